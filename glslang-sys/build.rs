@@ -26,6 +26,9 @@ pub fn main() {
 
     println!("cargo:rerun-if-changed=native/glslang");
     println!("cargo:rerun-if-changed=native/build_info");
+    println!("cargo:rerun-if-changed=native/shim");
+
+    check_build_info();
 
     let mut glslang_build = cc::Build::new();
     glslang_build
@@ -33,10 +36,25 @@ pub fn main() {
         .std("c++17")
         .define("ENABLE_SPIRV", "ON")
         .define("ENABLE_HLSL", "ON")
-        .define("ENABLE_OPT", "OFF")
         .define("ENABLE_GLSLANG_BINARIES", "OFF")
         .includes(&["native/glslang", "native/build_info"]);
 
+    #[cfg(feature = "spirv-tools")]
+    let spirv_tools = build_spirv_tools();
+
+    #[cfg(feature = "spirv-tools")]
+    glslang_build
+        .define("GLSLANG_RS_SPIRV_TOOLS", "1")
+        .include("native/SPIRV-Tools/include");
+
+    // glslang legalizes HLSL with SPIRV-Tools whenever ENABLE_OPT is set,
+    // so only the optimizer enables it.
+    #[cfg(feature = "optimizer")]
+    glslang_build.define("ENABLE_OPT", "1");
+
+    #[cfg(not(feature = "optimizer"))]
+    glslang_build.define("ENABLE_OPT", "OFF");
+
     add_subdirectory(&mut glslang_build, "glslang/CInterface");
     add_subdirectory(&mut glslang_build, "glslang/GenericCodeGen");
     add_subdirectory(&mut glslang_build, "glslang/HLSL");
@@ -44,6 +62,105 @@ pub fn main() {
 
     add_subdirectory(&mut glslang_build, "SPIRV");
 
+    glslang_build.file("native/shim/glslang_rs.cpp");
+
     glslang_build.compile("glslang");
     println!("cargo:rustc-link-lib=static=glslang");
+
+    #[cfg(feature = "spirv-tools")]
+    link_spirv_tools(&spirv_tools);
+}
+
+/// Check that the checked in `build_info.h` is for the version of the glslang submodule.
+///
+/// The shim mirrors private glslang definitions and asserts the version in `build_info.h`, so
+/// updating the submodule has to fail the build until `build_info.h`, the bindings and the shim
+/// are updated with it.
+fn check_build_info() {
+    let changes = std::fs::read_to_string("native/glslang/CHANGES.md")
+        .expect("failed to read the glslang changelog, run `git submodule update --init`");
+    // glslang generates build_info.h from the first release in the changelog.
+    let submodule_version = changes
+        .lines()
+        .find_map(|line| line.strip_prefix("## "))
+        .and_then(|line| line.split_whitespace().next())
+        .expect("failed to find the version in the glslang changelog");
+
+    let build_info = std::fs::read_to_string("native/build_info/glslang/build_info.h")
+        .expect("failed to read build_info.h");
+    let define = |name: &str| -> u32 {
+        build_info
+            .lines()
+            .find_map(|line| {
+                line.strip_prefix("#define ")?
+                    .strip_prefix(name)?
+                    .trim()
+                    .parse()
+                    .ok()
+            })
+            .unwrap_or_else(|| panic!("failed to find {name} in build_info.h"))
+    };
+    let build_info_version = format!(
+        "{}.{}.{}",
+        define("GLSLANG_VERSION_MAJOR"),
+        define("GLSLANG_VERSION_MINOR"),
+        define("GLSLANG_VERSION_PATCH")
+    );
+
+    if submodule_version != build_info_version {
+        panic!(
+            "the glslang submodule is version {submodule_version}, but build_info.h is for \
+            {build_info_version}. Regenerate build_info.h and the bindings with bindings_generator, \
+            and update the definitions mirrored by the shim"
+        );
+    }
+}
+
+/// Build the vendored SPIRV-Tools, returning the install directory.
+#[cfg(feature = "spirv-tools")]
+fn build_spirv_tools() -> std::path::PathBuf {
+    const SPIRV_TOOLS: &str = "native/SPIRV-Tools";
+    const SPIRV_HEADERS: &str = "native/SPIRV-Headers";
+
+    for submodule in [SPIRV_TOOLS, SPIRV_HEADERS] {
+        if !std::path::Path::new(submodule)
+            .join("CMakeLists.txt")
+            .exists()
+        {
+            panic!(
+                "the spirv-tools feature requires the {submodule} submodule, \
+                run `git submodule update --init` to check it out"
+            );
+        }
+    }
+
+    println!("cargo:rerun-if-changed={SPIRV_TOOLS}");
+    println!("cargo:rerun-if-changed={SPIRV_HEADERS}");
+
+    let headers = std::fs::canonicalize(SPIRV_HEADERS).expect("failed to find SPIRV-Headers");
+
+    cmake::Config::new(SPIRV_TOOLS)
+        .define("SPIRV-Headers_SOURCE_DIR", headers)
+        .define("SPIRV_SKIP_TESTS", "ON")
+        .define("SPIRV_SKIP_EXECUTABLES", "ON")
+        .define("SPIRV_WERROR", "OFF")
+        .define("SPIRV_TOOLS_BUILD_STATIC", "ON")
+        .build()
+}
+
+#[cfg(feature = "spirv-tools")]
+fn link_spirv_tools(install_dir: &std::path::Path) {
+    println!(
+        "cargo:rustc-link-search=native={}",
+        install_dir.join("lib").display()
+    );
+    println!(
+        "cargo:rustc-link-search=native={}",
+        install_dir.join("lib64").display()
+    );
+
+    // SPIRV-Tools-opt depends on SPIRV-Tools, so it has to come first.
+    #[cfg(feature = "optimizer")]
+    println!("cargo:rustc-link-lib=static=SPIRV-Tools-opt");
+    println!("cargo:rustc-link-lib=static=SPIRV-Tools");
 }
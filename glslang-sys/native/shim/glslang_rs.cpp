@@ -0,0 +1,495 @@
+#include "glslang_rs.h"
+
+#include "glslang/build_info.h"
+#include "glslang/Public/ShaderLang.h"
+#include "SPIRV/disassemble.h"
+#include "SPIRV/doc.h"
+#include "SPIRV/SPVRemapper.h"
+
+#include "glslang/MachineIndependent/localintermediate.h"
+
+#if ENABLE_OPT
+#include "SPIRV/SpvTools.h"
+#include "spirv-tools/optimizer.hpp"
+#endif
+
+#if GLSLANG_RS_SPIRV_TOOLS
+#include "spirv-tools/libspirv.hpp"
+#endif
+
+#include <algorithm>
+#include <cstdint>
+#include <cstdlib>
+#include <cstring>
+#include <mutex>
+#include <sstream>
+#include <string>
+#include <vector>
+
+// The mirrored structs below and check_disassembly depend on private code of this glslang version.
+// build.rs checks that build_info.h matches the glslang submodule, so updating the submodule fails here
+// until they are reviewed against the new version.
+static_assert(GLSLANG_VERSION_MAJOR == 13 && GLSLANG_VERSION_MINOR == 1 && GLSLANG_VERSION_PATCH == 1,
+              "the shim has not been updated for the vendored glslang version");
+
+// Mirrors the private definition in glslang/CInterface/glslang_c_interface.cpp.
+// This must be kept in sync with the vendored glslang version.
+typedef struct glslang_shader_s {
+    glslang::TShader* shader;
+    std::string preprocessedGLSL;
+    std::vector<std::string> baseResourceSetBinding;
+} glslang_shader_t;
+
+// Mirrors the private definition in glslang/CInterface/glslang_c_interface.cpp.
+typedef struct glslang_program_s {
+    glslang::TProgram* program;
+    std::vector<unsigned int> spirv;
+    std::string loggerMessages;
+} glslang_program_t;
+
+GLSLANG_EXPORT void glslang_rs_shader_set_entry_point(glslang_shader_t* shader, const char* name)
+{
+    shader->shader->setEntryPoint(name);
+}
+
+GLSLANG_EXPORT void glslang_rs_shader_set_source_entry_point(glslang_shader_t* shader, const char* name)
+{
+    shader->shader->setSourceEntryPoint(name);
+}
+
+GLSLANG_EXPORT void glslang_rs_shader_set_hlsl_io_mapping(glslang_shader_t* shader, bool enable)
+{
+    shader->shader->setHlslIoMapping(enable);
+}
+
+GLSLANG_EXPORT void glslang_rs_shader_set_flatten_uniform_arrays(glslang_shader_t* shader, bool enable)
+{
+    shader->shader->setFlattenUniformArrays(enable);
+}
+
+GLSLANG_EXPORT void glslang_rs_shader_set_invert_y(glslang_shader_t* shader, bool enable)
+{
+    shader->shader->setInvertY(enable);
+}
+
+namespace {
+
+// Resolves includes with the callbacks of a glslang_input_t.
+class CallbackIncluder : public glslang::TShader::Includer {
+public:
+    CallbackIncluder(glsl_include_callbacks_t callbacks, void* context) : callbacks(callbacks), context(context) {}
+
+    IncludeResult* includeSystem(const char* headerName, const char* includerName, size_t inclusionDepth) override
+    {
+        return include(callbacks.include_system, headerName, includerName, inclusionDepth);
+    }
+
+    IncludeResult* includeLocal(const char* headerName, const char* includerName, size_t inclusionDepth) override
+    {
+        return include(callbacks.include_local, headerName, includerName, inclusionDepth);
+    }
+
+    void releaseInclude(IncludeResult* result) override
+    {
+        if (result == nullptr)
+            return;
+
+        if (callbacks.free_include_result)
+            callbacks.free_include_result(context, static_cast<glsl_include_result_t*>(result->userData));
+        delete result;
+    }
+
+private:
+    IncludeResult* include(glsl_include_system_func callback, const char* headerName, const char* includerName,
+                           size_t inclusionDepth)
+    {
+        // Without a context there is no include handler to call.
+        if (callback == nullptr || context == nullptr)
+            return nullptr;
+
+        glsl_include_result_t* result = callback(context, headerName, includerName, inclusionDepth);
+        if (result == nullptr)
+            return nullptr;
+
+        // Named after the requested header like the includer of glslang_shader_preprocess.
+        return new IncludeResult(headerName, result->header_data, result->header_length, result);
+    }
+
+    glsl_include_callbacks_t callbacks;
+    void* context;
+};
+
+} // namespace
+
+GLSLANG_EXPORT int glslang_rs_shader_parse(glslang_shader_t* shader, const glslang_input_t* input)
+{
+    shader->shader->setStrings(&input->code, 1);
+    CallbackIncluder includer(input->callbacks, input->callbacks_ctx);
+
+    // glslang_profile_t and glslang_messages_t have the same values as EProfile and EShMessages.
+    return shader->shader->parse(reinterpret_cast<const TBuiltInResource*>(input->resource),
+                                 input->default_version,
+                                 static_cast<EProfile>(input->default_profile),
+                                 input->force_default_version_and_profile != 0,
+                                 input->forward_compatible != 0,
+                                 static_cast<EShMessages>(input->messages),
+                                 includer);
+}
+
+GLSLANG_EXPORT void glslang_rs_program_SPIRV_clear(glslang_program_t* program)
+{
+    program->spirv.clear();
+    program->loggerMessages.clear();
+}
+
+static char* copy_string(const std::string& string)
+{
+    char* result = static_cast<char*>(malloc(string.size() + 1));
+    memcpy(result, string.c_str(), string.size() + 1);
+    return result;
+}
+
+GLSLANG_EXPORT void glslang_rs_free_string(char* string)
+{
+    free(string);
+}
+
+// Returns the number of words of the nul-terminated string at begin, or 0 if it is not terminated before end.
+static size_t string_length(const unsigned int* words, size_t begin, size_t end)
+{
+    for (size_t word = begin; word < end; ++word) {
+        // Read in memory order like SpirvStream::disassembleString.
+        const char* bytes = reinterpret_cast<const char*>(&words[word]);
+        if (std::memchr(bytes, 0, sizeof(unsigned int)) != nullptr)
+            return word - begin + 1;
+    }
+
+    return 0;
+}
+
+// spv::Disassemble trusts the module: it exits the process on a bad schema or <id>, indexes its tables
+// with unchecked <id>s, and reads past the end of short instructions and unterminated strings. This walks
+// the operands of every instruction like SpirvStream in SPIRV/disassemble.cpp, so it has to be kept in
+// sync with the vendored glslang. Returns the first problem found, or an empty string.
+static std::string check_disassembly(const unsigned int* words, size_t size)
+{
+    const unsigned int bound = words[3];
+    if (words[4] != 0)
+        return "bad schema " + std::to_string(words[4]) + ", must be 0";
+
+    spv::Parameterize();
+
+    size_t word = 5;
+    while (word < size) {
+        const size_t start = word;
+        const size_t word_count = words[word] >> spv::WordCountShift;
+        const spv::Op opcode = static_cast<spv::Op>(words[word] & spv::OpCodeMask);
+        const spv::InstructionParameters& desc = spv::InstructionDesc[opcode];
+        const size_t end = start + word_count;
+
+        const std::string instruction = "instruction at word " + std::to_string(start);
+        if (word_count == 0 || end > size)
+            return instruction + " ends after the end of the module";
+
+        // The disassembler reads the type, result and required operands without checking the word count.
+        size_t min_word_count = 1 + desc.hasType() + desc.hasResult();
+        for (int op = 0; op < desc.operands.getNum(); ++op) {
+            switch (desc.operands.getClass(op)) {
+            case spv::OperandVariableIds:
+            case spv::OperandOptionalLiteral:
+            case spv::OperandOptionalLiteralString:
+            case spv::OperandVariableLiterals:
+            case spv::OperandVariableIdLiteral:
+            case spv::OperandVariableLiteralId:
+            case spv::OperandVariableLiteralStrings:
+                break;
+            default:
+                if (!desc.operands.isOptional(op))
+                    ++min_word_count;
+                break;
+            }
+        }
+
+        if (word_count < min_word_count) {
+            return instruction + " has " + std::to_string(word_count) + " words, but " + spv::OpcodeString(opcode) +
+                   " has at least " + std::to_string(min_word_count);
+        }
+
+        auto check_id = [&](size_t index) -> std::string {
+            if (words[index] < bound)
+                return "";
+            return instruction + " uses <id> " + std::to_string(words[index]) + ", which is not less than the bound " +
+                   std::to_string(bound);
+        };
+
+        // Checks every stride-th word from begin to the end of the instruction.
+        auto check_ids = [&](size_t begin, size_t stride) -> std::string {
+            for (size_t index = begin; index < end; index += stride) {
+                std::string error = check_id(index);
+                if (!error.empty())
+                    return error;
+            }
+            return "";
+        };
+
+        ++word;
+        std::string error;
+        for (size_t index = 0; index < size_t(desc.hasType()) + size_t(desc.hasResult()); ++index) {
+            error = check_id(word++);
+            if (!error.empty())
+                return error;
+        }
+
+        // The disassembler asserts on widths it has no name for.
+        if (opcode == spv::OpTypeInt || opcode == spv::OpTypeFloat) {
+            const unsigned int width = words[word];
+            if (width != 16 && width != 32 && width != 64 && !(opcode == spv::OpTypeInt && width == 8))
+                return instruction + " has an unsupported width of " + std::to_string(width);
+        }
+
+        // Only the sampled type of an image is an <id>, the rest are read as literals.
+        if (opcode == spv::OpTypeImage) {
+            error = check_id(word);
+            if (!error.empty())
+                return error;
+            word = end;
+            continue;
+        }
+
+        for (int op = 0; op < desc.operands.getNum() && word < end; ++op) {
+            switch (desc.operands.getClass(op)) {
+            case spv::OperandId:
+            case spv::OperandScope:
+            case spv::OperandMemorySemantics:
+                error = check_id(word++);
+                break;
+            case spv::OperandVariableIds:
+                error = check_ids(word, 1);
+                word = end;
+                break;
+            case spv::OperandImageOperands:
+                error = check_ids(word + 1, 1);
+                word = end;
+                break;
+            case spv::OperandOptionalLiteral:
+            case spv::OperandVariableLiterals:
+                word = end;
+                break;
+            case spv::OperandVariableIdLiteral:
+            case spv::OperandVariableLiteralId:
+                // Read as pairs of a literal and an <id>, even if the last pair is incomplete.
+                if ((end - word) % 2 != 0)
+                    return instruction + " ends with an incomplete pair of operands";
+                error = check_ids(desc.operands.getClass(op) == spv::OperandVariableIdLiteral ? word : word + 1, 2);
+                word = end;
+                break;
+            case spv::OperandOptionalLiteralString:
+            case spv::OperandLiteralString:
+            case spv::OperandVariableLiteralStrings: {
+                const bool variable = desc.operands.getClass(op) == spv::OperandVariableLiteralStrings;
+                do {
+                    const size_t length = string_length(words, word, end);
+                    if (length == 0)
+                        return instruction + " has a string that is not terminated";
+                    word += length;
+                } while (variable && word < end);
+                break;
+            }
+            case spv::OperandMemoryAccess:
+                // The alignment of an aligned access is a literal, the rest are <id>s.
+                if ((words[word++] & spv::MemoryAccessAlignedMask) != 0) {
+                    if (word == end)
+                        return instruction + " is missing the alignment of the memory access";
+                    ++word;
+                }
+                error = check_ids(word, 1);
+                word = end;
+                break;
+            default:
+                ++word;
+                break;
+            }
+
+            if (!error.empty())
+                return error;
+        }
+
+        word = end;
+    }
+
+    return "";
+}
+
+GLSLANG_EXPORT char* glslang_rs_spirv_disassemble(const unsigned int* words, size_t size, char** text)
+{
+    std::string error = check_disassembly(words, size);
+    if (!error.empty())
+        return copy_string(error);
+
+    std::vector<unsigned int> spirv(words, words + size);
+    std::ostringstream out;
+    spv::Disassemble(out, spirv);
+
+    *text = copy_string(out.str());
+    return nullptr;
+}
+
+// Remaps the module in place, returning the error reported by the remapper, if any.
+static std::string remap_spirv(std::vector<std::uint32_t>& spirv, unsigned int options)
+{
+    // The remapper error handler is global, so only one module can be remapped at a time.
+    static std::mutex remap_mutex;
+    static std::string remap_error;
+    std::lock_guard<std::mutex> lock(remap_mutex);
+
+    // The default error handler exits the process.
+    remap_error.clear();
+    spv::spirvbin_t::registerErrorHandler([](const std::string& message) {
+        if (remap_error.empty())
+            remap_error = message;
+    });
+
+    spv::spirvbin_t remapper;
+    remapper.remap(spirv, options);
+
+    return remap_error;
+}
+
+GLSLANG_EXPORT char* glslang_rs_spirv_remap(unsigned int* words, size_t* size, unsigned int options)
+{
+    std::vector<std::uint32_t> spirv(words, words + *size);
+    std::string error = remap_spirv(spirv, options);
+    if (!error.empty())
+        return copy_string(error);
+
+    // Remapping only removes or renumbers instructions, but check rather than overflow.
+    if (spirv.size() > *size)
+        return copy_string("remapped module is larger than the input");
+
+    std::copy(spirv.begin(), spirv.end(), words);
+    *size = spirv.size();
+    return nullptr;
+}
+
+GLSLANG_EXPORT char* glslang_rs_program_SPIRV_strip_debug_info(glslang_program_t* program)
+{
+    std::vector<std::uint32_t> spirv(program->spirv.begin(), program->spirv.end());
+    std::string error = remap_spirv(spirv, spv::spirvbin_t::STRIP);
+    if (!error.empty())
+        return copy_string(error);
+
+    program->spirv.assign(spirv.begin(), spirv.end());
+    return nullptr;
+}
+
+GLSLANG_EXPORT char* glslang_rs_program_SPIRV_optimize(glslang_program_t* program, glslang_stage_t stage, bool optimize_size)
+{
+#if ENABLE_OPT
+    // glslang_stage_t has the same values as EShLanguage.
+    const glslang::TIntermediate* intermediate = program->program->getIntermediate(static_cast<EShLanguage>(stage));
+    if (intermediate == nullptr)
+        return copy_string("the program has no intermediate for the stage");
+
+    spv::SpvBuildLogger logger;
+    spvtools::Optimizer optimizer(glslang::MapToSpirvToolsEnv(intermediate->getSpv(), &logger));
+
+    std::string messages;
+    optimizer.SetMessageConsumer([&messages](spv_message_level_t, const char*, const spv_position_t&, const char* message) {
+        messages += message;
+        messages += '\n';
+    });
+
+    if (optimize_size)
+        optimizer.RegisterSizePasses();
+    else
+        optimizer.RegisterPerformancePasses();
+
+    std::vector<std::uint32_t> optimized;
+    if (!optimizer.Run(program->spirv.data(), program->spirv.size(), &optimized))
+        return copy_string(messages.empty() ? "the optimizer failed" : messages);
+
+    program->spirv.assign(optimized.begin(), optimized.end());
+    return nullptr;
+#else
+    (void)program;
+    (void)stage;
+    (void)optimize_size;
+    return copy_string("glslang was built without the optimizer");
+#endif
+}
+
+#if GLSLANG_RS_SPIRV_TOOLS
+// Maps the target of the intermediate to a SPIRV-Tools environment. Unlike glslang::MapToSpirvToolsEnv,
+// this is available without ENABLE_OPT, and validates modules without a client against their SPIR-V version.
+static spv_target_env spirv_tools_env(const glslang::SpvVersion& version)
+{
+    switch (version.vulkan) {
+    case glslang::EShTargetVulkan_1_0:
+        return SPV_ENV_VULKAN_1_0;
+    case glslang::EShTargetVulkan_1_1:
+        return version.spv >= glslang::EShTargetSpv_1_4 ? SPV_ENV_VULKAN_1_1_SPIRV_1_4 : SPV_ENV_VULKAN_1_1;
+    case glslang::EShTargetVulkan_1_2:
+        return SPV_ENV_VULKAN_1_2;
+    case glslang::EShTargetVulkan_1_3:
+        return SPV_ENV_VULKAN_1_3;
+    default:
+        break;
+    }
+
+    if (version.openGl > 0)
+        return SPV_ENV_OPENGL_4_5;
+
+    switch (version.spv) {
+    case glslang::EShTargetSpv_1_1:
+        return SPV_ENV_UNIVERSAL_1_1;
+    case glslang::EShTargetSpv_1_2:
+        return SPV_ENV_UNIVERSAL_1_2;
+    case glslang::EShTargetSpv_1_3:
+        return SPV_ENV_UNIVERSAL_1_3;
+    case glslang::EShTargetSpv_1_4:
+        return SPV_ENV_UNIVERSAL_1_4;
+    case glslang::EShTargetSpv_1_5:
+        return SPV_ENV_UNIVERSAL_1_5;
+    case glslang::EShTargetSpv_1_6:
+        return SPV_ENV_UNIVERSAL_1_6;
+    default:
+        return SPV_ENV_UNIVERSAL_1_0;
+    }
+}
+#endif
+
+GLSLANG_EXPORT char* glslang_rs_program_SPIRV_validate(glslang_program_t* program, glslang_stage_t stage, bool hlsl_legalized)
+{
+#if GLSLANG_RS_SPIRV_TOOLS
+    // glslang_stage_t has the same values as EShLanguage.
+    const glslang::TIntermediate* intermediate = program->program->getIntermediate(static_cast<EShLanguage>(stage));
+    if (intermediate == nullptr)
+        return copy_string("error: the program has no intermediate for the stage\n");
+
+    // The same options as glslang::SpirvToolsValidate.
+    spvtools::ValidatorOptions options;
+    options.SetRelaxBlockLayout(intermediate->usingHlslOffsets());
+    options.SetBeforeHlslLegalization(intermediate->getSource() == glslang::EShSourceHlsl && !hlsl_legalized);
+    options.SetScalarBlockLayout(intermediate->usingScalarBlockLayout());
+    options.SetWorkgroupScalarBlockLayout(intermediate->usingScalarBlockLayout());
+
+    std::string messages;
+    spvtools::SpirvTools tools(spirv_tools_env(intermediate->getSpv()));
+    tools.SetMessageConsumer([&messages](spv_message_level_t, const char*, const spv_position_t&, const char* message) {
+        messages += "error: ";
+        messages += message;
+        messages += '\n';
+    });
+
+    if (tools.Validate(program->spirv.data(), program->spirv.size(), options))
+        return nullptr;
+
+    // Logged like glslang::SpirvToolsValidate, so the errors parse the same way.
+    return copy_string("error: SPIRV-Tools Validation Errors\n" + messages);
+#else
+    (void)program;
+    (void)stage;
+    (void)hlsl_legalized;
+    return copy_string("error: glslang was built without SPIRV-Tools\n");
+#endif
+}
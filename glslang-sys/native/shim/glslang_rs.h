@@ -0,0 +1,58 @@
+#ifndef GLSLANG_RS_SHIM_H
+#define GLSLANG_RS_SHIM_H
+
+// Small extensions to the glslang C interface for functionality that is only
+// reachable through the C++ API.
+
+#include "../glslang/glslang/Include/glslang_c_interface.h"
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+GLSLANG_EXPORT void glslang_rs_shader_set_entry_point(glslang_shader_t* shader, const char* name);
+GLSLANG_EXPORT void glslang_rs_shader_set_source_entry_point(glslang_shader_t* shader, const char* name);
+GLSLANG_EXPORT void glslang_rs_shader_set_hlsl_io_mapping(glslang_shader_t* shader, bool enable);
+GLSLANG_EXPORT void glslang_rs_shader_set_flatten_uniform_arrays(glslang_shader_t* shader, bool enable);
+GLSLANG_EXPORT void glslang_rs_shader_set_invert_y(glslang_shader_t* shader, bool enable);
+
+// Parses the source of the input with the preamble and include callbacks of the shader.
+// Unlike glslang_shader_parse, this does not parse the preprocessed code, where the preamble
+// precedes the #version directive, and honours the default profile and
+// force_default_version_and_profile of the input.
+GLSLANG_EXPORT int glslang_rs_shader_parse(glslang_shader_t* shader, const glslang_input_t* input);
+
+// Clears the SPIR-V of the program. glslang appends each generated module to the
+// previous ones, so this must be called before generating SPIR-V again.
+GLSLANG_EXPORT void glslang_rs_program_SPIRV_clear(glslang_program_t* program);
+
+// Strings returned by the shim must be released with glslang_rs_free_string.
+GLSLANG_EXPORT void glslang_rs_free_string(char* string);
+
+// Disassembles a SPIR-V module like glslangValidator -H, setting text to the disassembly.
+// The module must have a header and complete instructions. The schema, <id>s and operands of the
+// instructions are checked first, since glslang does not. Returns NULL on success, or the problem found.
+GLSLANG_EXPORT char* glslang_rs_spirv_disassemble(const unsigned int* words, size_t size, char** text);
+
+// Remaps a SPIR-V module in place with spv::spirvbin_t, updating size to the remapped length.
+// Returns NULL on success, or the error reported by the remapper.
+GLSLANG_EXPORT char* glslang_rs_spirv_remap(unsigned int* words, size_t* size, unsigned int options);
+
+// Strips debug information from the SPIR-V of the program with spv::spirvbin_t, which is
+// available without SPIRV-Tools. Returns NULL on success, or the error reported by the remapper.
+GLSLANG_EXPORT char* glslang_rs_program_SPIRV_strip_debug_info(glslang_program_t* program);
+
+// Runs the SPIRV-Tools performance or size passes, like spirv-opt -O or -Os, over the SPIR-V
+// of the program. Returns NULL on success, or the messages of the optimizer.
+GLSLANG_EXPORT char* glslang_rs_program_SPIRV_optimize(glslang_program_t* program, glslang_stage_t stage, bool optimize_size);
+
+// Validates the SPIR-V of the program with SPIRV-Tools for the target environment of the stage, like
+// spirv-val. hlsl_legalized tells the validator whether SPIR-V generated from HLSL was legalized.
+// Returns NULL if the module is valid, otherwise the validation errors in the format glslang logs them.
+GLSLANG_EXPORT char* glslang_rs_program_SPIRV_validate(glslang_program_t* program, glslang_stage_t stage, bool hlsl_legalized);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // GLSLANG_RS_SHIM_H
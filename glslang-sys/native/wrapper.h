@@ -1 +1,2 @@
-#include "glslang_c_interface.h"
\ No newline at end of file
+#include "glslang_c_interface.h"
+#include "shim/glslang_rs.h"
\ No newline at end of file
@@ -713,3 +713,72 @@ extern "C" {
 extern "C" {
     pub fn glslang_glsl_resolver_delete(resolver: *mut glslang_resolver_t);
 }
+extern "C" {
+    pub fn glslang_rs_shader_set_entry_point(
+        shader: *mut glslang_shader_t,
+        name: *const ::std::os::raw::c_char,
+    );
+}
+extern "C" {
+    pub fn glslang_rs_shader_set_source_entry_point(
+        shader: *mut glslang_shader_t,
+        name: *const ::std::os::raw::c_char,
+    );
+}
+extern "C" {
+    pub fn glslang_rs_shader_set_hlsl_io_mapping(shader: *mut glslang_shader_t, enable: bool);
+}
+extern "C" {
+    pub fn glslang_rs_shader_set_flatten_uniform_arrays(
+        shader: *mut glslang_shader_t,
+        enable: bool,
+    );
+}
+extern "C" {
+    pub fn glslang_rs_shader_set_invert_y(shader: *mut glslang_shader_t, enable: bool);
+}
+extern "C" {
+    pub fn glslang_rs_shader_parse(
+        shader: *mut glslang_shader_t,
+        input: *const glslang_input_t,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn glslang_rs_program_SPIRV_clear(program: *mut glslang_program_t);
+}
+extern "C" {
+    pub fn glslang_rs_free_string(string: *mut ::std::os::raw::c_char);
+}
+extern "C" {
+    pub fn glslang_rs_spirv_disassemble(
+        words: *const ::std::os::raw::c_uint,
+        size: usize,
+        text: *mut *mut ::std::os::raw::c_char,
+    ) -> *mut ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn glslang_rs_spirv_remap(
+        words: *mut ::std::os::raw::c_uint,
+        size: *mut usize,
+        options: ::std::os::raw::c_uint,
+    ) -> *mut ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn glslang_rs_program_SPIRV_strip_debug_info(
+        program: *mut glslang_program_t,
+    ) -> *mut ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn glslang_rs_program_SPIRV_optimize(
+        program: *mut glslang_program_t,
+        stage: glslang_stage_t,
+        optimize_size: bool,
+    ) -> *mut ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn glslang_rs_program_SPIRV_validate(
+        program: *mut glslang_program_t,
+        stage: glslang_stage_t,
+        hlsl_legalized: bool,
+    ) -> *mut ::std::os::raw::c_char;
+}
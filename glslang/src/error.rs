@@ -1,6 +1,8 @@
 use crate::ctypes::ShaderStage;
-use crate::shader::Target;
-use crate::GlslProfile;
+use crate::hlsl::HlslProfile;
+use crate::shader::{OpenGlEsVersion, OpenGlVersion, Target};
+use crate::validation::Diagnostic;
+use crate::{GlslProfile, SourceLanguage, SpirvVersion};
 use thiserror::Error;
 
 /// The error logs
@@ -27,10 +29,12 @@ impl std::fmt::Display for GlslangErrorLog {
 /// The error type for `glslang`.
 #[derive(Debug, Error)]
 pub enum GlslangError {
-    /// Error occurred when preprocessing.
+    /// Error occurred when preprocessing with [`Compiler::preprocess`](crate::Compiler::preprocess).
+    ///
+    /// Creating a [`Shader`](crate::Shader) reports preprocessor errors as [`GlslangError::ParseError`].
     #[error("preprocess error: {0}")]
     PreprocessError(GlslangErrorLog),
-    /// Error occurred when preprocessing.
+    /// Error occurred when parsing.
     #[error("parse error: {0}")]
     ParseError(GlslangErrorLog),
     /// Error occurred when mapping IO.
@@ -45,13 +49,81 @@ pub enum GlslangError {
     /// No SPIR-V language target was set.
     #[error("tried to compile shader with no language target")]
     NoLanguageTarget,
-    /// The target is not a valid combination of environment, version, and language version.
-    #[error("the target is invalid")]
-    InvalidTarget(Target),
+    /// The target is not a valid combination of environment, version, and language version,
+    /// or can not be used with the source language or shader stage.
+    #[error("invalid target {0:?}: {1}")]
+    InvalidTarget(Target, TargetError),
     /// The GLSL profile and version is not valid for the specified target.
-    #[error("the profile is invalid")]
+    #[error("GLSL version {1} with profile {2:?} is not valid for target {0:?}")]
     InvalidProfile(Target, i32, GlslProfile),
+    /// The `#version` directive of the source could not be parsed.
+    #[error("invalid #version directive")]
+    InvalidVersionDirective,
     /// The GLSL version is unsupported for the profile
-    #[error("the profile is invalid")]
+    #[error("GLSL version {0} is not supported with profile {1:?}")]
     VersionUnsupported(i32, GlslProfile),
+    /// HLSL options or messages were specified for a source that is not HLSL.
+    #[error("HLSL options are not valid for {0:?} sources")]
+    UnexpectedHlslOptions(SourceLanguage),
+    /// The string is not a valid HLSL target profile.
+    #[error("invalid HLSL profile: {0}")]
+    InvalidHlslProfile(String),
+    /// The register of an HLSL resource set binding is not a register name.
+    #[error("invalid HLSL register: {0}")]
+    InvalidHlslRegister(String),
+    /// The HLSL target profile can not be compiled by glslang.
+    #[error("unsupported HLSL profile: {0}")]
+    UnsupportedHlslProfile(HlslProfile),
+    /// The macro name, parameter list, or value can not be used in a `#define`.
+    #[error("invalid macro define: {0}")]
+    InvalidMacroDefine(String),
+    /// The macro was defined more than once.
+    #[error("macro defined more than once: {0}")]
+    DuplicateMacroDefine(String),
+    /// SPIR-V optimization was requested, but glslang was built without the `optimizer` feature.
+    #[error("SPIR-V optimization requires the `optimizer` feature")]
+    OptimizerUnavailable,
+    /// SPIRV-Tools failed to optimize the generated SPIR-V.
+    #[error("SPIR-V optimization error: {0}")]
+    OptimizeError(String),
+    /// SPIR-V validation was requested, but glslang was built without the `spirv-validation` feature.
+    #[error("SPIR-V validation requires the `spirv-validation` feature")]
+    SpirvValidatorUnavailable,
+    /// The generated SPIR-V failed validation with SPIRV-Tools.
+    #[error("SPIR-V validation failed: {}", .0.iter().map(|d| d.message.as_str()).collect::<Vec<_>>().join("\n"))]
+    SpirvValidation(Vec<Diagnostic>),
+    /// The words are not a well-formed SPIR-V module.
+    #[error("invalid SPIR-V module: {0}")]
+    InvalidSpirv(String),
+    /// The SPIR-V remapper failed to remap the module.
+    #[error("SPIR-V remap error: {0}")]
+    RemapError(String),
+    /// A shader was added to a program containing shaders for a different target.
+    #[error("shader target {1:?} does not match the program target {0:?}")]
+    IncompatibleTarget(Target, Target),
+}
+
+/// The reason a [`Target`](crate::Target) is invalid.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TargetError {
+    /// The SPIR-V version is newer than the client version supports.
+    #[error("{spirv_version:?} is not supported, the client supports up to {max_spirv_version:?}")]
+    UnsupportedSpirvVersion {
+        /// The requested SPIR-V version.
+        spirv_version: SpirvVersion,
+        /// The highest SPIR-V version supported by the client.
+        max_spirv_version: SpirvVersion,
+    },
+    /// SPIR-V can not be generated for the OpenGL version.
+    #[error("OpenGL SPIR-V requires OpenGL 4.5 or higher, but the target is {0:?}")]
+    OpenGlSpirvUnsupported(OpenGlVersion),
+    /// HLSL sources can not be compiled for OpenGL or OpenGL ES.
+    #[error("HLSL sources can not be compiled for OpenGL or OpenGL ES")]
+    HlslForOpenGl,
+    /// The shader stage requires a newer target.
+    #[error("{0:?} shaders require Vulkan with SPIR-V 1.4 or higher")]
+    UnsupportedStage(ShaderStage),
+    /// The shader stage is not available in the OpenGL ES version.
+    #[error("{0:?} shaders are not supported by {1:?}")]
+    UnsupportedOpenGlEsStage(ShaderStage, OpenGlEsVersion),
 }
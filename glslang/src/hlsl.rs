@@ -0,0 +1,225 @@
+use crate::ctypes::ShaderStage;
+use crate::error::GlslangError;
+use crate::shader::{CompilerOptions, HlslOptions, Target, VulkanVersion};
+use crate::SourceLanguage;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// An HLSL shader model version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShaderModel {
+    /// The major version of the shader model.
+    pub major: u8,
+    /// The minor version of the shader model.
+    pub minor: u8,
+}
+
+impl ShaderModel {
+    /// Create a new shader model version.
+    pub const fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+
+    fn is_known(&self) -> bool {
+        matches!(
+            (self.major, self.minor),
+            (2, 0) | (3, 0) | (4, 0) | (4, 1) | (5, 0) | (5, 1) | (6, 0..=8)
+        )
+    }
+}
+
+impl Display for ShaderModel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}_{}", self.major, self.minor)
+    }
+}
+
+/// A DXC-style HLSL target profile, such as `ps_6_0` or `cs_5_1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HlslProfile {
+    /// The shader stage of the profile.
+    ///
+    /// This is `None` for library profiles (`lib_x_y`), which can not be compiled by glslang.
+    pub stage: Option<ShaderStage>,
+    /// The shader model of the profile.
+    pub shader_model: ShaderModel,
+}
+
+impl HlslProfile {
+    /// The minimum shader model for the given stage prefix.
+    fn minimum_shader_model(stage: Option<ShaderStage>) -> ShaderModel {
+        match stage {
+            None => ShaderModel::new(6, 3),
+            Some(ShaderStage::Mesh | ShaderStage::Task) => ShaderModel::new(6, 5),
+            Some(ShaderStage::TesselationControl | ShaderStage::TesselationEvaluation) => {
+                ShaderModel::new(5, 0)
+            }
+            Some(ShaderStage::Geometry | ShaderStage::Compute) => ShaderModel::new(4, 0),
+            Some(_) => ShaderModel::new(2, 0),
+        }
+    }
+
+    /// The default Vulkan version for the shader model, targeting the highest SPIR-V version
+    /// it supports.
+    ///
+    /// Shader model 6.0 introduced wave intrinsics which require subgroup operations from
+    /// SPIR-V 1.3, and mesh and amplification shaders from shader model 6.5 require at least
+    /// SPIR-V 1.4, so newer shader models target newer Vulkan versions.
+    pub fn default_target(&self) -> Target {
+        let version = match self.shader_model {
+            sm if sm >= ShaderModel::new(6, 6) => VulkanVersion::Vulkan1_3,
+            sm if sm >= ShaderModel::new(6, 5) => VulkanVersion::Vulkan1_2,
+            sm if sm >= ShaderModel::new(6, 0) => VulkanVersion::Vulkan1_1,
+            _ => VulkanVersion::Vulkan1_0,
+        };
+
+        Target::vulkan(version)
+    }
+
+    /// The default HLSL options for the shader model.
+    ///
+    /// Native 16-bit types are enabled from shader model 6.2, and DirectX 9 compatibility
+    /// is enabled for shader models before 4.0.
+    pub fn default_hlsl_options(&self) -> HlslOptions {
+        HlslOptions {
+            enable_16bit_types: self.shader_model >= ShaderModel::new(6, 2),
+            dx9_compatible: self.shader_model < ShaderModel::new(4, 0),
+            ..HlslOptions::default()
+        }
+    }
+
+    /// Compiler options for compiling HLSL with this profile.
+    pub fn compiler_options(&self) -> CompilerOptions {
+        CompilerOptions {
+            source_language: SourceLanguage::HLSL,
+            target: self.default_target(),
+            hlsl: Some(self.default_hlsl_options()),
+            ..CompilerOptions::default()
+        }
+    }
+}
+
+impl FromStr for HlslProfile {
+    type Err = GlslangError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || GlslangError::InvalidHlslProfile(s.to_string());
+
+        let mut parts = s.split('_');
+        let (Some(prefix), Some(major), Some(minor), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        let stage = match prefix {
+            "vs" => Some(ShaderStage::Vertex),
+            "hs" => Some(ShaderStage::TesselationControl),
+            "ds" => Some(ShaderStage::TesselationEvaluation),
+            "gs" => Some(ShaderStage::Geometry),
+            "ps" => Some(ShaderStage::Fragment),
+            "cs" => Some(ShaderStage::Compute),
+            "ms" => Some(ShaderStage::Mesh),
+            "as" => Some(ShaderStage::Task),
+            "lib" => None,
+            _ => return Err(invalid()),
+        };
+
+        let (Ok(major), Ok(minor)) = (major.parse::<u8>(), minor.parse::<u8>()) else {
+            return Err(invalid());
+        };
+
+        let shader_model = ShaderModel::new(major, minor);
+        if !shader_model.is_known() || shader_model < Self::minimum_shader_model(stage) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            stage,
+            shader_model,
+        })
+    }
+}
+
+impl Display for HlslProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let prefix = match self.stage {
+            Some(ShaderStage::Vertex) => "vs",
+            Some(ShaderStage::TesselationControl) => "hs",
+            Some(ShaderStage::TesselationEvaluation) => "ds",
+            Some(ShaderStage::Geometry) => "gs",
+            Some(ShaderStage::Fragment) => "ps",
+            Some(ShaderStage::Compute) => "cs",
+            Some(ShaderStage::Mesh) => "ms",
+            Some(ShaderStage::Task) => "as",
+            Some(_) | None => "lib",
+        };
+        write!(f, "{}_{}", prefix, self.shader_model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpirvVersion;
+
+    #[test]
+    pub fn test_parse_profiles() {
+        let profiles = [
+            ("vs_6_0", Some(ShaderStage::Vertex), ShaderModel::new(6, 0)),
+            (
+                "ps_5_1",
+                Some(ShaderStage::Fragment),
+                ShaderModel::new(5, 1),
+            ),
+            ("cs_6_6", Some(ShaderStage::Compute), ShaderModel::new(6, 6)),
+            ("lib_6_3", None, ShaderModel::new(6, 3)),
+            ("ms_6_5", Some(ShaderStage::Mesh), ShaderModel::new(6, 5)),
+            ("as_6_5", Some(ShaderStage::Task), ShaderModel::new(6, 5)),
+        ];
+
+        for (string, stage, shader_model) in profiles {
+            let profile = HlslProfile::from_str(string).expect("profile");
+            assert_eq!(profile.stage, stage);
+            assert_eq!(profile.shader_model, shader_model);
+            assert_eq!(profile.to_string(), string);
+        }
+    }
+
+    #[test]
+    pub fn test_parse_invalid_profiles() {
+        for string in [
+            "", "ps", "ps_6", "ps_6_0_1", "xs_6_0", "ps_6_x", "ms_6_0", "ps_7_0",
+        ] {
+            assert!(matches!(
+                HlslProfile::from_str(string),
+                Err(GlslangError::InvalidHlslProfile(_))
+            ));
+        }
+    }
+
+    #[test]
+    pub fn test_profile_defaults() {
+        let profile = HlslProfile::from_str("ps_6_2").expect("profile");
+        let options = profile.compiler_options();
+        assert_eq!(options.source_language, SourceLanguage::HLSL);
+        assert!(options.hlsl.is_some_and(|hlsl| hlsl.enable_16bit_types));
+        assert!(matches!(
+            options.target,
+            Target::Vulkan {
+                version: VulkanVersion::Vulkan1_1,
+                spirv_version: SpirvVersion::SPIRV1_3,
+            }
+        ));
+
+        let profile = HlslProfile::from_str("ps_5_0").expect("profile");
+        assert!(matches!(
+            profile.default_target(),
+            Target::Vulkan {
+                version: VulkanVersion::Vulkan1_0,
+                spirv_version: SpirvVersion::SPIRV1_0,
+            }
+        ));
+        assert!(!profile.default_hlsl_options().enable_16bit_types);
+    }
+}
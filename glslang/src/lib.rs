@@ -5,12 +5,16 @@ mod ctypes;
 /// Error types.
 pub mod error;
 
+mod hlsl;
 /// Helpers for processing includes.
 pub mod include;
 /// Shader resouce limits.
 pub mod limits;
 mod program;
 mod shader;
+/// Tools for working with SPIR-V modules.
+pub mod spirv;
+mod validation;
 
 static COMPILER_INSTANCE: OnceLock<Option<Compiler>> = OnceLock::new();
 
@@ -19,8 +23,10 @@ pub struct Compiler;
 
 pub use crate::ctypes::*;
 
-pub use program::Program;
+pub use hlsl::{HlslProfile, ShaderModel};
+pub use program::{LinkedProgram, OptimizationLevel, Program, SpirvOptions};
 pub use shader::*;
+pub use validation::{Diagnostic, DiagnosticSeverity, ValidationReport};
 
 impl Compiler {
     /// Acquire a global instance of the compiler.
@@ -42,6 +48,24 @@ impl Compiler {
         Shader::new(&self, input)
     }
 
+    /// Run only the preprocessor on the given inputs, returning the preprocessed source.
+    pub fn preprocess(&self, input: ShaderInput) -> Result<String, error::GlslangError> {
+        Shader::preprocess(self, input)
+    }
+
+    /// Parse the given inputs and report their diagnostics, without generating SPIR-V.
+    ///
+    /// If `link` is true and all shaders were parsed successfully, the shaders are also
+    /// linked together. Parse and link failures are reported in the [`ValidationReport`],
+    /// an error is only returned if the shaders could not be validated at all.
+    pub fn validate<'input>(
+        &self,
+        inputs: impl IntoIterator<Item = ShaderInput<'input>>,
+        link: bool,
+    ) -> Result<ValidationReport, error::GlslangError> {
+        ValidationReport::validate(self, inputs, link)
+    }
+
     /// Create a [`Program`](crate::Program) instance.
     pub fn create_program(&self) -> Program {
         Program::new(&self)
@@ -1,3 +1,4 @@
+use crate::{OpenGlEsVersion, OpenGlVersion, WebGlVersion};
 use glslang_sys as sys;
 
 /// Limits on what structural items a shader is allowed to have.
@@ -6,6 +7,25 @@ pub use sys::glslang_limits_t as CompilerLimits;
 /// Default resource limits allowed by glslang.
 pub const DEFAULT_LIMITS: ResourceLimits = ResourceLimits::default_limits();
 
+const OPENGL_ES_2_0_LIMITS: ResourceLimits =
+    ResourceLimits::opengl_es(OpenGlEsVersion::OpenGLES2_0);
+const OPENGL_ES_3_0_LIMITS: ResourceLimits =
+    ResourceLimits::opengl_es(OpenGlEsVersion::OpenGLES3_0);
+const OPENGL_ES_3_1_LIMITS: ResourceLimits =
+    ResourceLimits::opengl_es(OpenGlEsVersion::OpenGLES3_1);
+const OPENGL_ES_3_2_LIMITS: ResourceLimits =
+    ResourceLimits::opengl_es(OpenGlEsVersion::OpenGLES3_2);
+
+/// The minimum resource limits of the OpenGL ES version, which outlive any shader input.
+pub(crate) fn opengl_es_limits(version: OpenGlEsVersion) -> &'static ResourceLimits {
+    match version {
+        OpenGlEsVersion::OpenGLES2_0 => &OPENGL_ES_2_0_LIMITS,
+        OpenGlEsVersion::OpenGLES3_0 => &OPENGL_ES_3_0_LIMITS,
+        OpenGlEsVersion::OpenGLES3_1 => &OPENGL_ES_3_1_LIMITS,
+        OpenGlEsVersion::OpenGLES3_2 => &OPENGL_ES_3_2_LIMITS,
+    }
+}
+
 /// Specifies resource limits allowed by each shader.
 #[repr(transparent)]
 #[derive(Clone)]
@@ -132,6 +152,187 @@ impl ResourceLimits {
         })
     }
 }
+impl ResourceLimits {
+    /// The minimum resource limits guaranteed by the OpenGL ES version.
+    ///
+    /// For OpenGL ES 2.0, the compiler limits are restricted to the minimum functionality
+    /// required by Appendix A of the GLSL ES 1.00 specification.
+    pub const fn opengl_es(version: OpenGlEsVersion) -> Self {
+        let mut limits = Self::default_limits().0;
+
+        // OpenGL ES 2.0
+        limits.max_vertex_attribs = 8;
+        limits.max_vertex_uniform_vectors = 128;
+        limits.max_varying_vectors = 8;
+        limits.max_vertex_texture_image_units = 0;
+        limits.max_combined_texture_image_units = 8;
+        limits.max_texture_image_units = 8;
+        limits.max_fragment_uniform_vectors = 16;
+        limits.max_draw_buffers = 1;
+
+        if let OpenGlEsVersion::OpenGLES2_0 = version {
+            limits.limits = CompilerLimits {
+                non_inductive_for_loops: false,
+                while_loops: false,
+                do_while_loops: false,
+                general_uniform_indexing: false,
+                general_attribute_matrix_vector_indexing: false,
+                general_varying_indexing: false,
+                general_sampler_indexing: false,
+                general_variable_indexing: false,
+                general_constant_matrix_vector_indexing: false,
+            };
+            return Self(limits);
+        }
+
+        // OpenGL ES 3.0
+        limits.max_vertex_attribs = 16;
+        limits.max_vertex_uniform_vectors = 256;
+        limits.max_vertex_uniform_components = 1024;
+        limits.max_varying_vectors = 15;
+        limits.max_varying_components = 60;
+        limits.max_vertex_output_vectors = 16;
+        limits.max_vertex_output_components = 64;
+        limits.max_fragment_input_vectors = 15;
+        limits.max_fragment_input_components = 60;
+        limits.max_vertex_texture_image_units = 16;
+        limits.max_combined_texture_image_units = 32;
+        limits.max_texture_image_units = 16;
+        limits.max_fragment_uniform_vectors = 224;
+        limits.max_fragment_uniform_components = 896;
+        limits.max_draw_buffers = 4;
+        limits.min_program_texel_offset = -8;
+        limits.max_program_texel_offset = 7;
+
+        if let OpenGlEsVersion::OpenGLES3_0 = version {
+            return Self(limits);
+        }
+
+        // OpenGL ES 3.1
+        limits.max_compute_work_group_count_x = 65535;
+        limits.max_compute_work_group_count_y = 65535;
+        limits.max_compute_work_group_count_z = 65535;
+        limits.max_compute_work_group_size_x = 128;
+        limits.max_compute_work_group_size_y = 128;
+        limits.max_compute_work_group_size_z = 64;
+        limits.max_compute_uniform_components = 512;
+        limits.max_compute_texture_image_units = 16;
+        limits.max_compute_image_uniforms = 4;
+        limits.max_compute_atomic_counters = 8;
+        limits.max_compute_atomic_counter_buffers = 1;
+        limits.max_image_units = 4;
+        limits.max_vertex_image_uniforms = 0;
+        limits.max_fragment_image_uniforms = 0;
+        limits.max_combined_image_uniforms = 4;
+        limits.max_combined_shader_output_resources = 4;
+        limits.max_vertex_atomic_counters = 0;
+        limits.max_fragment_atomic_counters = 0;
+        limits.max_combined_atomic_counters = 8;
+        limits.max_atomic_counter_bindings = 1;
+        limits.max_vertex_atomic_counter_buffers = 0;
+        limits.max_fragment_atomic_counter_buffers = 0;
+        limits.max_combined_atomic_counter_buffers = 1;
+        limits.max_atomic_counter_buffer_size = 32;
+
+        if let OpenGlEsVersion::OpenGLES3_1 = version {
+            return Self(limits);
+        }
+
+        // OpenGL ES 3.2
+        limits.max_combined_texture_image_units = 96;
+        limits.max_geometry_input_components = 64;
+        limits.max_geometry_output_components = 64;
+        limits.max_geometry_output_vertices = 256;
+        limits.max_geometry_total_output_components = 1024;
+        limits.max_geometry_uniform_components = 1024;
+        limits.max_geometry_texture_image_units = 16;
+        limits.max_tess_control_input_components = 64;
+        limits.max_tess_control_output_components = 64;
+        limits.max_tess_control_texture_image_units = 16;
+        limits.max_tess_control_uniform_components = 1024;
+        limits.max_tess_control_total_output_components = 2048;
+        limits.max_tess_evaluation_input_components = 64;
+        limits.max_tess_evaluation_output_components = 64;
+        limits.max_tess_evaluation_texture_image_units = 16;
+        limits.max_tess_evaluation_uniform_components = 1024;
+        limits.max_tess_patch_components = 120;
+        limits.max_patch_vertices = 32;
+        limits.max_tess_gen_level = 64;
+
+        Self(limits)
+    }
+
+    /// The resource limits for the OpenGL version.
+    ///
+    /// These are the default limits, with the limits of features that are unavailable in
+    /// the OpenGL version set to zero.
+    pub const fn opengl(version: OpenGlVersion) -> Self {
+        let mut limits = Self::default_limits().0;
+        let glsl_version = version.glsl_version();
+
+        if glsl_version >= 430 {
+            return Self(limits);
+        }
+
+        // OpenGL 4.2 has no compute shaders
+        limits.max_compute_work_group_count_x = 0;
+        limits.max_compute_work_group_count_y = 0;
+        limits.max_compute_work_group_count_z = 0;
+        limits.max_compute_work_group_size_x = 0;
+        limits.max_compute_work_group_size_y = 0;
+        limits.max_compute_work_group_size_z = 0;
+        limits.max_compute_uniform_components = 0;
+        limits.max_compute_texture_image_units = 0;
+        limits.max_compute_image_uniforms = 0;
+        limits.max_compute_atomic_counters = 0;
+        limits.max_compute_atomic_counter_buffers = 0;
+
+        if glsl_version >= 420 {
+            return Self(limits);
+        }
+
+        // OpenGL 4.1 has no image load/store or atomic counters
+        limits.max_image_units = 0;
+        limits.max_combined_image_units_and_fragment_outputs = 0;
+        limits.max_combined_shader_output_resources = 0;
+        limits.max_fragment_image_uniforms = 0;
+        limits.max_combined_image_uniforms = 0;
+        limits.max_fragment_atomic_counters = 0;
+        limits.max_combined_atomic_counters = 0;
+        limits.max_atomic_counter_bindings = 0;
+        limits.max_fragment_atomic_counter_buffers = 0;
+        limits.max_combined_atomic_counter_buffers = 0;
+        limits.max_atomic_counter_buffer_size = 0;
+
+        if glsl_version >= 400 {
+            return Self(limits);
+        }
+
+        // OpenGL 3.3 has no tessellation shaders
+        limits.max_combined_texture_image_units = 48;
+        limits.max_viewports = 1;
+        limits.max_tess_control_input_components = 0;
+        limits.max_tess_control_output_components = 0;
+        limits.max_tess_control_texture_image_units = 0;
+        limits.max_tess_control_uniform_components = 0;
+        limits.max_tess_control_total_output_components = 0;
+        limits.max_tess_evaluation_input_components = 0;
+        limits.max_tess_evaluation_output_components = 0;
+        limits.max_tess_evaluation_texture_image_units = 0;
+        limits.max_tess_evaluation_uniform_components = 0;
+        limits.max_tess_patch_components = 0;
+        limits.max_patch_vertices = 0;
+        limits.max_tess_gen_level = 0;
+
+        Self(limits)
+    }
+
+    /// The minimum resource limits guaranteed by the WebGL version.
+    pub const fn webgl(version: WebGlVersion) -> Self {
+        Self::opengl_es(version.opengl_es_version())
+    }
+}
+
 impl Default for ResourceLimits {
     fn default() -> Self {
         Self::default_limits()
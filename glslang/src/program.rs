@@ -1,17 +1,24 @@
 use crate::ctypes::ShaderStage;
 use crate::error::{GlslangError, GlslangErrorLog};
-use crate::{Compiler, Shader};
+use crate::spirv::{SpirvModule, SpirvView, HEADER_LENGTH};
+use crate::validation::Diagnostic;
+use crate::{Compiler, Shader, ShaderMessage, Target};
 use glslang_sys as sys;
 use glslang_sys::glslang_spv_options_s;
 use rustc_hash::FxHashMap;
 use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
 use std::ptr::NonNull;
+use std::sync::Arc;
 
 /// Lower-level program interface.
 pub struct Program<'a> {
     handle: NonNull<sys::glslang_program_t>,
     cache: FxHashMap<ShaderStage, bool>,
+    target: Option<Target>,
+    messages: ShaderMessage,
+    // Dropped after the program handle is deleted in Drop.
+    shaders: Vec<Arc<Shader<'a>>>,
     _compiler: PhantomData<&'a Compiler>,
 }
 
@@ -23,6 +30,9 @@ impl<'a> Program<'a> {
                 NonNull::new(sys::glslang_program_create()).expect("glslang created null shader")
             },
             cache: FxHashMap::default(),
+            target: None,
+            messages: ShaderMessage::DEFAULT,
+            shaders: Vec::new(),
             _compiler: PhantomData,
         };
 
@@ -30,12 +40,65 @@ impl<'a> Program<'a> {
     }
 
     /// Add a shader to the program. The lifetime of the shader must extend beyond the lifetime of the program instance.
-    pub fn add_shader<'shader>(&mut self, shader: &'shader Shader<'shader>)
+    ///
+    /// All shaders in a program must be created for the same [`Target`](crate::Target), otherwise
+    /// [`GlslangError::IncompatibleTarget`] is returned.
+    pub fn add_shader<'shader>(
+        &mut self,
+        shader: &'shader Shader<'shader>,
+    ) -> Result<(), GlslangError>
     where
         'shader: 'a,
     {
+        self.attach_shader(shader)
+    }
+
+    /// Add a shader to the program, keeping it alive for as long as the program.
+    ///
+    /// Unlike [`add_shader`](Self::add_shader), this does not tie the lifetime of the program to a
+    /// borrow of the shader, so a program can be built and returned from a function. The shader
+    /// can be given by value or as an [`Arc`] shared with other programs, and retrieved with
+    /// [`shaders`](Self::shaders) or [`LinkedProgram::into_shaders`].
+    pub fn add_owned_shader(
+        &mut self,
+        shader: impl Into<Arc<Shader<'a>>>,
+    ) -> Result<(), GlslangError> {
+        let shader = shader.into();
+        self.attach_shader(&shader)?;
+        self.shaders.push(shader);
+        Ok(())
+    }
+
+    /// The shaders owned by the program, in the order they were added with
+    /// [`add_owned_shader`](Self::add_owned_shader).
+    pub fn shaders(&self) -> &[Arc<Shader<'a>>] {
+        &self.shaders
+    }
+
+    fn attach_shader(&mut self, shader: &Shader) -> Result<(), GlslangError> {
+        match &self.target {
+            Some(target) if *target != shader.target => {
+                return Err(GlslangError::IncompatibleTarget(
+                    target.clone(),
+                    shader.target.clone(),
+                ));
+            }
+            Some(_) => {}
+            None => self.target = Some(shader.target.clone()),
+        }
+
         unsafe { sys::glslang_program_add_shader(self.handle.as_ptr(), shader.handle.as_ptr()) }
         self.cache.insert(shader.stage, shader.is_spirv);
+        self.messages |= shader.link_messages;
+        Ok(())
+    }
+
+    /// The messages the program is linked with by [`link`](Self::link).
+    ///
+    /// These are the messages of the [`CompilerOptions`](crate::CompilerOptions) of every shader in
+    /// the program, with the rules required by their target.
+    pub fn link_messages(&self) -> ShaderMessage {
+        self.messages
     }
 
     /// Map shader input/output locations. Requires [crate::ShaderOptions::AUTO_MAP_LOCATIONS] to be set
@@ -48,139 +111,392 @@ impl<'a> Program<'a> {
         Ok(())
     }
 
-    /// Link the program without compiling to SPIR-V.
+    /// Link the program, returning a [`LinkedProgram`](crate::LinkedProgram) that can generate
+    /// SPIR-V for each stage of the program.
+    ///
+    /// The program is linked with the messages of its shaders, see [`link_messages`](Self::link_messages).
+    ///
+    /// A [`Program`](crate::Program) can only be linked once.
+    pub fn link(self) -> Result<LinkedProgram<'a>, GlslangError> {
+        let messages = self.messages;
+        self.link_with_messages(messages)
+    }
+
+    /// Link the program with the given messages instead of the messages of its shaders.
     ///
     /// A [`Program`](crate::Program) can only be linked once.
-    pub fn link(self) -> Result<(), GlslangError> {
-        let messages = glslang_sys::glslang_messages_t::DEFAULT
-            | glslang_sys::glslang_messages_t::VULKAN_RULES
-            | glslang_sys::glslang_messages_t::SPV_RULES;
+    pub fn link_with_messages(
+        mut self,
+        messages: ShaderMessage,
+    ) -> Result<LinkedProgram<'a>, GlslangError> {
+        self.link_in_place(messages)?;
+        Ok(LinkedProgram { program: self })
+    }
 
+    /// Link the program with the given messages, keeping the program to read the log.
+    pub(crate) fn link_in_place(&mut self, messages: ShaderMessage) -> Result<(), GlslangError> {
+        let messages = sys::glslang_messages_t::from(messages);
         if unsafe { sys::glslang_program_link(self.handle.as_ptr(), messages.0) } == 0 {
-            return Err(GlslangError::LinkError(GlslangErrorLog::new(self.get_log(), self.get_debug_log())));
+            return Err(GlslangError::LinkError(GlslangErrorLog::new(
+                self.get_log(),
+                self.get_debug_log(),
+            )));
         }
         Ok(())
     }
 
-    /// Compile the given stage to SPIR-V, consuming the program.
+    /// Link the program and compile the given stage to SPIR-V, consuming the program.
     ///
-    /// A [`Program`](crate::Program) can not be re-used to compile multiple stages.
-    pub fn compile(self, stage: ShaderStage) -> Result<Vec<u32>, GlslangError> {
-        // If the stage was not previously added to the program, compiling SPIRV ends up segfaulting.
-        if !self.cache.contains_key(&stage) {
-            return Err(GlslangError::ShaderStageNotFound(stage));
-        }
+    /// To compile multiple stages of the same program, use [`link`](Self::link) instead.
+    pub fn compile(self, stage: ShaderStage) -> Result<SpirvModule, GlslangError> {
+        self.link()?.compile(stage)
+    }
 
-        if let Some(false) = self.cache.get(&stage) {
-            return Err(GlslangError::NoLanguageTarget);
-        }
+    /// Link the program and compile the given stage to SPIR-V, optimizing for size, consuming
+    /// the program.
+    ///
+    /// To compile multiple stages of the same program, use [`link`](Self::link) instead.
+    pub fn compile_size_optimized(self, stage: ShaderStage) -> Result<SpirvModule, GlslangError> {
+        self.link()?.compile_size_optimized(stage)
+    }
 
-        let messages = glslang_sys::glslang_messages_t::DEFAULT
-            | glslang_sys::glslang_messages_t::VULKAN_RULES
-            | glslang_sys::glslang_messages_t::SPV_RULES;
+    /// Link the program and compile the given stage to SPIR-V with the given options, consuming
+    /// the program.
+    ///
+    /// To compile multiple stages of the same program, use [`link`](Self::link) instead.
+    pub fn compile_with_options(
+        self,
+        stage: ShaderStage,
+        options: &SpirvOptions,
+    ) -> Result<SpirvModule, GlslangError> {
+        self.link()?.compile_with_options(stage, options)
+    }
 
-        if unsafe { sys::glslang_program_link(self.handle.as_ptr(), messages.0) } == 0 {
-            return Err(GlslangError::LinkError(GlslangErrorLog::new(self.get_log(), self.get_debug_log())));
-        }
+    pub fn get_log(&self) -> String {
+        let c_str =
+            unsafe { CStr::from_ptr(sys::glslang_program_get_info_log(self.handle.as_ptr())) };
+
+        let string = CString::from(c_str)
+            .into_string()
+            .expect("Expected glslang info log to be valid UTF-8");
 
-        // We don't support SPIRV compile options because nearly all of them (except for generateDebugInfo),
-        // require callbacks that either we don't expose, or are not exposed by the C API.
-        // disableOptimizer is redundant as well because we need to support WASM, which doesn't support
-        // the optimizer.
-        unsafe { sys::glslang_program_SPIRV_generate(self.handle.as_ptr(), stage) }
+        string
+    }
 
-        let size = unsafe { sys::glslang_program_SPIRV_get_size(self.handle.as_ptr()) };
-        let mut buffer = vec![0u32; size];
+    pub fn get_debug_log(&self) -> String {
+        let c_str =
+            unsafe { CStr::from_ptr(sys::glslang_program_get_info_debug_log(self.handle.as_ptr())) };
 
-        unsafe {
-            sys::glslang_program_SPIRV_get(self.handle.as_ptr(), buffer.as_mut_ptr());
-        }
+        let string = CString::from(c_str)
+            .into_string()
+            .expect("Expected glslang info log to be valid UTF-8");
+
+        string
+    }
+}
+
+impl<'a> Drop for Program<'a> {
+    fn drop(&mut self) {
+        unsafe { sys::glslang_program_delete(self.handle.as_ptr()) }
+    }
+}
+
+/// A linked program, which can generate SPIR-V for any of its stages.
+pub struct LinkedProgram<'a> {
+    program: Program<'a>,
+}
 
-        Ok(buffer)
+impl<'a> LinkedProgram<'a> {
+    /// Compile the given stage to SPIR-V.
+    pub fn compile(&self, stage: ShaderStage) -> Result<SpirvModule, GlslangError> {
+        self.compile_with_options(stage, &SpirvOptions::default())
     }
 
-    /// Compile the given stage to SPIR-V, optimizing for size, consuming the program.
+    /// Compile the given stage to SPIR-V, optimizing for size.
     ///
-    /// A [`Program`](crate::Program) can not be re-used to compile multiple stages.
-    pub fn compile_size_optimized(self, stage: ShaderStage) -> Result<Vec<u32>, GlslangError> {
-        // If the stage was not previously added to the program, compiling SPIRV ends up segfaulting.
-        if !self.cache.contains_key(&stage) {
-            return Err(GlslangError::ShaderStageNotFound(stage));
-        }
+    /// Without the `optimizer` feature, this is the same as [`LinkedProgram::compile`].
+    pub fn compile_size_optimized(&self, stage: ShaderStage) -> Result<SpirvModule, GlslangError> {
+        let optimization_level = if cfg!(feature = "optimizer") {
+            OptimizationLevel::Size
+        } else {
+            OptimizationLevel::None
+        };
 
-        if let Some(false) = self.cache.get(&stage) {
-            return Err(GlslangError::NoLanguageTarget);
+        self.compile_with_options(
+            stage,
+            &SpirvOptions {
+                optimization_level,
+                ..SpirvOptions::default()
+            },
+        )
+    }
+
+    /// Compile the given stage to SPIR-V with the given options.
+    ///
+    /// Returns [`GlslangError::OptimizerUnavailable`] if an optimization level is set
+    /// without the `optimizer` feature.
+    pub fn compile_with_options(
+        &self,
+        stage: ShaderStage,
+        options: &SpirvOptions,
+    ) -> Result<SpirvModule, GlslangError> {
+        self.generate(stage, options)?;
+        self.get_spirv()
+    }
+
+    /// Compile the given stage to SPIR-V, borrowing the words from the program instead of copying them.
+    ///
+    /// glslang overwrites the words whenever the program generates SPIR-V, so the program
+    /// is borrowed mutably for as long as the view is alive.
+    pub fn compile_view(&mut self, stage: ShaderStage) -> Result<SpirvView<'_>, GlslangError> {
+        self.compile_view_with_options(stage, &SpirvOptions::default())
+    }
+
+    /// Compile the given stage to SPIR-V with the given options, borrowing the words from the program
+    /// instead of copying them.
+    ///
+    /// See [`compile_view`](Self::compile_view) and [`compile_with_options`](Self::compile_with_options).
+    pub fn compile_view_with_options(
+        &mut self,
+        stage: ShaderStage,
+        options: &SpirvOptions,
+    ) -> Result<SpirvView<'_>, GlslangError> {
+        self.generate(stage, options)?;
+        self.get_spirv_view()
+    }
+
+    /// Compile every stage of the program to SPIR-V.
+    pub fn compile_all(&self) -> Result<FxHashMap<ShaderStage, SpirvModule>, GlslangError> {
+        self.program
+            .cache
+            .keys()
+            .map(|&stage| Ok((stage, self.compile(stage)?)))
+            .collect()
+    }
+
+    /// The stages of the program.
+    pub fn stages(&self) -> impl Iterator<Item = ShaderStage> + '_ {
+        self.program.cache.keys().copied()
+    }
+
+    /// The shaders owned by the program, see [`Program::shaders`].
+    pub fn shaders(&self) -> &[Arc<Shader<'a>>] {
+        self.program.shaders()
+    }
+
+    /// Release the program, returning the shaders it owned.
+    ///
+    /// Shaders that are not shared with other programs can be taken back with [`Arc::try_unwrap`].
+    pub fn into_shaders(mut self) -> Vec<Arc<Shader<'a>>> {
+        std::mem::take(&mut self.program.shaders)
+    }
+
+    pub fn get_log(&self) -> String {
+        self.program.get_log()
+    }
+
+    pub fn get_debug_log(&self) -> String {
+        self.program.get_debug_log()
+    }
+
+    fn check_stage(&self, stage: ShaderStage) -> Result<(), GlslangError> {
+        // If the stage was not previously added to the program, compiling SPIRV ends up segfaulting.
+        match self.program.cache.get(&stage) {
+            None => Err(GlslangError::ShaderStageNotFound(stage)),
+            Some(false) => Err(GlslangError::NoLanguageTarget),
+            Some(true) => Ok(()),
         }
+    }
 
-        let messages = glslang_sys::glslang_messages_t::DEFAULT
-            | glslang_sys::glslang_messages_t::VULKAN_RULES
-            | glslang_sys::glslang_messages_t::SPV_RULES;
+    fn generate(&self, stage: ShaderStage, options: &SpirvOptions) -> Result<(), GlslangError> {
+        self.check_stage(stage)?;
 
-        if unsafe { sys::glslang_program_link(self.handle.as_ptr(), messages.0) } == 0 {
-            return Err(GlslangError::LinkError(GlslangErrorLog::new(self.get_log(), self.get_debug_log())));
+        if !cfg!(feature = "optimizer") && options.optimization_level != OptimizationLevel::None {
+            return Err(GlslangError::OptimizerUnavailable);
         }
 
-        let mut options = glslang_spv_options_s {
-            generate_debug_info: false,
-            strip_debug_info: false,
-            disable_optimizer: false,
-            optimize_size: true,
-            disassemble: false,
-            validate: false,
-            emit_nonsemantic_shader_debug_info: false,
-            emit_nonsemantic_shader_debug_source: false,
-            compile_only: false,
-            optimize_allow_expanded_id_bound: false,
-        };
+        if !cfg!(feature = "spirv-validation") && options.validate {
+            return Err(GlslangError::SpirvValidatorUnavailable);
+        }
 
-        // We don't support SPIRV compile options because nearly all of them (except for generateDebugInfo),
-        // require callbacks that either we don't expose, or are not exposed by the C API.
-        // disableOptimizer is redundant as well because we need to support WASM, which doesn't support
-        // the optimizer.
+        let mut sys_options = options.to_sys();
+        self.clear_spirv();
         unsafe {
             sys::glslang_program_SPIRV_generate_with_options(
-                self.handle.as_ptr(),
+                self.program.handle.as_ptr(),
                 stage,
-                &mut options,
+                &mut sys_options,
             )
         }
 
-        let size = unsafe { sys::glslang_program_SPIRV_get_size(self.handle.as_ptr()) };
-        let mut buffer = vec![0u32; size];
+        match options.optimization_level {
+            OptimizationLevel::None => {}
+            OptimizationLevel::Performance => self.optimize(stage, false)?,
+            OptimizationLevel::Size => self.optimize(stage, true)?,
+        }
+
+        if options.strip_debug_info {
+            self.strip_debug_info()?;
+        }
+
+        // Validate the final module, after it was optimized and stripped.
+        if options.validate {
+            let hlsl_legalized = cfg!(feature = "optimizer") && !options.disable_optimizer;
+            self.validate_spirv(stage, hlsl_legalized)?;
+        }
+
+        Ok(())
+    }
+
+    fn clear_spirv(&self) {
+        // glslang appends every generated module to the same buffer.
+        unsafe { sys::glslang_rs_program_SPIRV_clear(self.program.handle.as_ptr()) }
+    }
 
+    fn optimize(&self, stage: ShaderStage, optimize_size: bool) -> Result<(), GlslangError> {
         unsafe {
-            sys::glslang_program_SPIRV_get(self.handle.as_ptr(), buffer.as_mut_ptr());
+            let error = sys::glslang_rs_program_SPIRV_optimize(
+                self.program.handle.as_ptr(),
+                stage,
+                optimize_size,
+            );
+            if !error.is_null() {
+                let message = CStr::from_ptr(error).to_string_lossy().into_owned();
+                sys::glslang_rs_free_string(error);
+                return Err(GlslangError::OptimizeError(message));
+            }
         }
 
-        Ok(buffer)
+        Ok(())
     }
 
-    pub fn get_log(&self) -> String {
-        let c_str =
-            unsafe { CStr::from_ptr(sys::glslang_program_get_info_log(self.handle.as_ptr())) };
+    fn strip_debug_info(&self) -> Result<(), GlslangError> {
+        unsafe {
+            let error =
+                sys::glslang_rs_program_SPIRV_strip_debug_info(self.program.handle.as_ptr());
+            if !error.is_null() {
+                let message = CStr::from_ptr(error).to_string_lossy().into_owned();
+                sys::glslang_rs_free_string(error);
+                return Err(GlslangError::RemapError(message));
+            }
+        }
 
-        let string = CString::from(c_str)
-            .into_string()
-            .expect("Expected glslang info log to be valid UTF-8");
+        Ok(())
+    }
 
-        string
+    fn validate_spirv(&self, stage: ShaderStage, hlsl_legalized: bool) -> Result<(), GlslangError> {
+        unsafe {
+            let messages = sys::glslang_rs_program_SPIRV_validate(
+                self.program.handle.as_ptr(),
+                stage,
+                hlsl_legalized,
+            );
+            if !messages.is_null() {
+                let diagnostics = Diagnostic::parse_spirv_messages(
+                    stage,
+                    &CStr::from_ptr(messages).to_string_lossy(),
+                );
+                sys::glslang_rs_free_string(messages);
+                return Err(GlslangError::SpirvValidation(diagnostics));
+            }
+        }
+
+        Ok(())
     }
 
-    pub fn get_debug_log(&self) -> String {
-        let c_str =
-            unsafe { CStr::from_ptr(sys::glslang_program_get_info_debug_log(self.handle.as_ptr())) };
+    fn get_spirv(&self) -> Result<SpirvModule, GlslangError> {
+        Ok(SpirvModule::from_words_unchecked(
+            self.get_spirv_view()?.into_vec(),
+        ))
+    }
 
-        let string = CString::from(c_str)
-            .into_string()
-            .expect("Expected glslang info log to be valid UTF-8");
+    fn get_spirv_view(&self) -> Result<SpirvView<'_>, GlslangError> {
+        let size = unsafe { sys::glslang_program_SPIRV_get_size(self.program.handle.as_ptr()) };
 
-        string
+        // A module is at least its header, which SpirvModule relies on.
+        if size < HEADER_LENGTH {
+            return Err(GlslangError::InvalidSpirv(format!(
+                "glslang generated {size} words of SPIR-V"
+            )));
+        }
+
+        let words = unsafe {
+            let ptr = sys::glslang_program_SPIRV_get_ptr(self.program.handle.as_ptr());
+            std::slice::from_raw_parts(ptr, size)
+        };
+
+        Ok(SpirvView::new(words))
     }
 }
 
-impl<'a> Drop for Program<'a> {
-    fn drop(&mut self) {
-        unsafe { sys::glslang_program_delete(self.handle.as_ptr()) }
+/// Options for generating SPIR-V from a linked program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SpirvOptions {
+    /// Emit `OpLine` and `OpSource` debug information for the source.
+    ///
+    /// The source text is only included if the shader was created with
+    /// [`ShaderMessage::DEBUG_INFO`](crate::ShaderMessage::DEBUG_INFO).
+    pub generate_debug_info: bool,
+    /// Strip all debug information, including `OpName` and `OpMemberName`.
+    ///
+    /// Stripping uses glslang's SPIR-V remapper like [`spirv::remap`](crate::spirv::remap) with
+    /// [`RemapOptions::STRIP`](crate::spirv::RemapOptions::STRIP), so it does not require the
+    /// `optimizer` feature.
+    pub strip_debug_info: bool,
+    /// Disable the legalization of SPIR-V generated from HLSL.
+    /// This has no effect unless glslang was built with the `optimizer` feature.
+    pub disable_optimizer: bool,
+    /// Optimize the SPIR-V with SPIRV-Tools. Requires the `optimizer` feature.
+    ///
+    /// With the `optimizer` feature, SPIR-V generated from HLSL is legalized for Vulkan even if
+    /// no optimization level is set, unless [`disable_optimizer`](Self::disable_optimizer) is set.
+    pub optimization_level: OptimizationLevel,
+    /// Print the disassembly of the generated SPIR-V to standard output.
+    pub disassemble: bool,
+    /// Validate the generated SPIR-V with SPIRV-Tools for the environment of the target, after it
+    /// was optimized and stripped. Requires the `spirv-validation` feature.
+    ///
+    /// Validation failures are returned as [`GlslangError::SpirvValidation`].
+    pub validate: bool,
+    /// Emit debug information with the `NonSemantic.Shader.DebugInfo.100` extended instruction set.
+    pub emit_nonsemantic_shader_debug_info: bool,
+    /// Include the source text in the `NonSemantic.Shader.DebugInfo.100` debug information.
+    pub emit_nonsemantic_shader_debug_source: bool,
+    /// Generate SPIR-V without entry points or linkage requirements, as a library module.
+    pub compile_only: bool,
+    /// Allow the optimizer to grow the id bound of the module beyond its current value.
+    pub optimize_allow_expanded_id_bound: bool,
+}
+
+/// The SPIRV-Tools optimization passes to run on generated SPIR-V.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OptimizationLevel {
+    /// Do not optimize the SPIR-V.
+    #[default]
+    None,
+    /// Run the SPIRV-Tools performance passes, like `spirv-opt -O`.
+    Performance,
+    /// Run the SPIRV-Tools size passes, like `spirv-opt -Os`.
+    Size,
+}
+
+impl SpirvOptions {
+    fn to_sys(&self) -> glslang_spv_options_s {
+        glslang_spv_options_s {
+            generate_debug_info: self.generate_debug_info,
+            strip_debug_info: self.strip_debug_info,
+            // With the optimizer, glslang legalizes SPIR-V generated from HLSL. The optimization
+            // passes of the level are run separately, since glslang only runs them for size.
+            disable_optimizer: self.disable_optimizer,
+            optimize_size: false,
+            disassemble: self.disassemble,
+            // The final module is validated after it was optimized and stripped.
+            validate: false,
+            emit_nonsemantic_shader_debug_info: self.emit_nonsemantic_shader_debug_info,
+            emit_nonsemantic_shader_debug_source: self.emit_nonsemantic_shader_debug_source,
+            compile_only: self.compile_only,
+            optimize_allow_expanded_id_bound: self.optimize_allow_expanded_id_bound,
+        }
     }
 }
 
@@ -189,8 +505,11 @@ mod tests {
     use super::*;
     use crate::ctypes::ShaderStage;
     use crate::include::{IncludeHandler, IncludeResult};
-    use crate::shader::{CompilerOptions, OpenGlVersion, ShaderInput, ShaderSource, Target};
-    use crate::{GlslProfile, ShaderMessage, SourceLanguage};
+    use crate::shader::{
+        CompilerOptions, HlslOptions, HlslRegisterBinding, HlslResourceSetBinding, OpenGlVersion,
+        ShaderInput, ShaderSource, Target, VulkanVersion,
+    };
+    use crate::{GlslProfile, ShaderMessage, SourceLanguage, SpirvVersion};
     use rspirv::binary::Disassemble;
 
     #[test]
@@ -222,7 +541,7 @@ void main() {
         let _shader = Shader::new(&compiler, input).expect("shader init");
 
         let program = Program::new(&compiler);
-        // program.add_shader(&shader);
+        // program.add_shader(&shader).expect("add shader");
 
         program.link().expect("shader");
     }
@@ -301,6 +620,9 @@ void main() {
                 },
                 messages: ShaderMessage::DEBUG_INFO | ShaderMessage::DEFAULT,
                 version_profile: Some((120, GlslProfile::None)),
+                default_version_profile: None,
+                forward_compatible: false,
+                hlsl: None,
             },
             None,
             None,
@@ -336,6 +658,9 @@ void main() {
                 target: Target::None(None),
                 messages: ShaderMessage::DEBUG_INFO | ShaderMessage::DEFAULT,
                 version_profile: None,
+                default_version_profile: None,
+                forward_compatible: false,
+                hlsl: None,
             },
             None,
             None,
@@ -406,13 +731,13 @@ void main()
 
         let mut program = Program::new(&compiler);
 
-        program.add_shader(&fragment);
-        program.add_shader(&vertex);
+        program.add_shader(&fragment).expect("add shader");
+        program.add_shader(&vertex).expect("add shader");
 
         let _code = program.compile(ShaderStage::Fragment).expect("shader");
 
         let mut program = compiler.create_program();
-        program.add_shader(&vertex);
+        program.add_shader(&vertex).expect("add shader");
         let code2 = program.compile(ShaderStage::Vertex).expect("shader");
 
         let mut loader = rspirv::dr::Loader::new();
@@ -423,91 +748,616 @@ void main()
     }
 
     #[test]
-    pub fn test_add_macros() {
+    pub fn test_compile_linked_program() {
         let compiler = Compiler::acquire().unwrap();
 
-        let source = ShaderSource::try_from(String::from(
+        let fragment = ShaderSource::from(
             r#"
-#version 460
+#version 450
 
+layout(location = 0) in vec2 vTexCoord;
 layout(location = 0) out vec4 color;
+layout(binding = 1) uniform sampler2D tex;
 
 void main() {
-    color = vec4(CUSTOM_MACRO);
+    color = texture(tex, vTexCoord);
 }
         "#,
-        ))
-        .expect("source");
+        );
 
-        let input = ShaderInput::new(
-            &source,
+        let vertex = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) in vec4 Position;
+layout(location = 1) in vec2 TexCoord;
+layout(location = 0) out vec2 vTexCoord;
+
+void main() {
+    gl_Position = Position;
+    vTexCoord = TexCoord;
+}
+        "#,
+        );
+
+        let fragment = ShaderInput::new(
+            &fragment,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            None,
+            None,
+        )
+        .expect("target");
+        let fragment = Shader::new(compiler, fragment).expect("shader init");
+
+        let vertex = ShaderInput::new(
+            &vertex,
             ShaderStage::Vertex,
-            &CompilerOptions {
-                source_language: SourceLanguage::GLSL,
-                target: Target::None(None),
-                messages: ShaderMessage::DEBUG_INFO | ShaderMessage::DEFAULT,
-                version_profile: None,
-            },
-            Some(&[("CUSTOM_MACRO", Some("1.0"))]),
+            &CompilerOptions::default(),
+            None,
             None,
         )
         .expect("target");
-        let _shader = Shader::new(&compiler, input).expect("shader init");
+        let vertex = Shader::new(compiler, vertex).expect("shader init");
+
+        let mut program = Program::new(compiler);
+        program.add_shader(&fragment).expect("add shader");
+        program.add_shader(&vertex).expect("add shader");
+        let program = program.link().expect("link");
+
+        let vertex_code = program.compile(ShaderStage::Vertex).expect("compile");
+        let fragment_code = program.compile(ShaderStage::Fragment).expect("compile");
+        assert_eq!(
+            program.compile(ShaderStage::Vertex).expect("compile"),
+            vertex_code
+        );
+        assert!(matches!(
+            program.compile(ShaderStage::Compute),
+            Err(GlslangError::ShaderStageNotFound(ShaderStage::Compute))
+        ));
+
+        let all = program.compile_all().expect("compile");
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[&ShaderStage::Vertex], vertex_code);
+        assert_eq!(all[&ShaderStage::Fragment], fragment_code);
     }
 
     #[test]
-    pub fn test_include_handler() {
+    pub fn test_compile_view() {
         let compiler = Compiler::acquire().unwrap();
 
-        struct MyIncludeHandler {
-            header_included: Vec<String>,
-        }
-        impl IncludeHandler for MyIncludeHandler {
-            fn include(
-                &mut self,
-                _ty: crate::include::IncludeType,
-                header_name: &str,
-                _includer_name: &str,
-                _include_depth: usize,
-            ) -> Option<IncludeResult> {
-                self.header_included.push(header_name.into());
-                Some(IncludeResult {
-                    name: "included_macro".into(),
-                    data: "#define INCLUDED_MACRO 0.0".into(),
-                })
-            }
-        }
-
-        let source = ShaderSource::try_from(String::from(
+        let source = ShaderSource::from(
             r#"
-#version 460
-#extension GL_GOOGLE_include_directive : require
-#include "custom_include.glsl"
+#version 450
 
-layout(location = 0) out vec4 color;
+layout(local_size_x = 64) in;
+
+layout(std430, binding = 0) buffer Data {
+    float values[];
+};
 
 void main() {
-    color = vec4(INCLUDED_MACRO);
+    values[gl_GlobalInvocationID.x] *= 2.0;
 }
         "#,
-        ))
-        .expect("source");
-        let mut include_handler = MyIncludeHandler {
-            header_included: vec![],
-        };
+        );
+
         let input = ShaderInput::new(
             &source,
-            ShaderStage::Vertex,
-            &CompilerOptions {
-                source_language: SourceLanguage::GLSL,
-                target: Target::OpenGL {
-                    version: OpenGlVersion::OpenGL4_5,
-                    spirv_version: None,
-                },
-                messages: ShaderMessage::DEBUG_INFO | ShaderMessage::DEFAULT,
-                version_profile: None,
-            },
-            Some(&[("CUSTOM_MACRO", Some("1.0"))]),
+            ShaderStage::Compute,
+            &CompilerOptions::default(),
+            None,
+            None,
+        )
+        .expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+
+        let mut program = Program::new(compiler);
+        program.add_shader(&shader).expect("add shader");
+        let mut program = program.link().expect("link");
+
+        let module = program.compile(ShaderStage::Compute).expect("compile");
+
+        // The view only covers the requested stage, not previously generated modules.
+        let view = program.compile_view(ShaderStage::Compute).expect("compile");
+        assert_eq!(view.words(), module.words());
+        assert_eq!(view.to_module(), module);
+        assert_eq!(view.into_vec(), module.clone().into_vec());
+
+        let options_module = program
+            .compile_with_options(ShaderStage::Compute, &SpirvOptions::default())
+            .expect("compile");
+        let view = program
+            .compile_view_with_options(ShaderStage::Compute, &SpirvOptions::default())
+            .expect("compile");
+        assert_eq!(view.words(), options_module.words());
+
+        assert!(matches!(
+            program.compile_view(ShaderStage::Fragment),
+            Err(GlslangError::ShaderStageNotFound(ShaderStage::Fragment))
+        ));
+    }
+
+    #[test]
+    pub fn test_link_messages() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let vertex = ShaderSource::from(
+            r#"
+#version 450
+
+void main() {
+    gl_Position = vec4(0.0);
+}
+        "#,
+        );
+
+        let fragment = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        let opengl = CompilerOptions {
+            target: Target::OpenGL {
+                version: OpenGlVersion::OpenGL4_5,
+                spirv_version: None,
+            },
+            messages: ShaderMessage::RELAXED_ERRORS,
+            ..CompilerOptions::default()
+        };
+
+        let input =
+            ShaderInput::new(&vertex, ShaderStage::Vertex, &opengl, None, None).expect("target");
+        let vertex_shader = Shader::new(compiler, input).expect("shader init");
+
+        let input = ShaderInput::new(&fragment, ShaderStage::Fragment, &opengl, None, None)
+            .expect("target");
+        let fragment_shader = Shader::new(compiler, input).expect("shader init");
+
+        let mut program = Program::new(compiler);
+        program.add_shader(&vertex_shader).expect("add shader");
+        program.add_shader(&fragment_shader).expect("add shader");
+        assert_eq!(program.link_messages(), ShaderMessage::RELAXED_ERRORS);
+        program.link().expect("link");
+
+        let input = ShaderInput::new(
+            &fragment,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            None,
+            None,
+        )
+        .expect("target");
+        let vulkan_shader = Shader::new(compiler, input).expect("shader init");
+
+        let mut program = Program::new(compiler);
+        program.add_shader(&vulkan_shader).expect("add shader");
+        assert!(program
+            .link_messages()
+            .contains(ShaderMessage::VULKAN_RULES | ShaderMessage::SPV_RULES));
+
+        assert!(matches!(
+            program.add_shader(&vertex_shader),
+            Err(GlslangError::IncompatibleTarget(_, Target::OpenGL { .. }))
+        ));
+
+        program
+            .link_with_messages(ShaderMessage::VULKAN_RULES | ShaderMessage::SPV_RULES)
+            .expect("link");
+    }
+
+    fn build_owned_program(vertex: &str, fragment: &str) -> LinkedProgram<'static> {
+        let compiler = Compiler::acquire().unwrap();
+        let mut program = Program::new(compiler);
+
+        for (source, stage) in [
+            (vertex, ShaderStage::Vertex),
+            (fragment, ShaderStage::Fragment),
+        ] {
+            let source = ShaderSource::from(source);
+            let input = ShaderInput::new(&source, stage, &CompilerOptions::default(), None, None)
+                .expect("target");
+            let shader = Shader::new(compiler, input).expect("shader init");
+            program.add_owned_shader(shader).expect("add shader");
+        }
+
+        program.link().expect("link")
+    }
+
+    #[test]
+    pub fn test_owned_shaders() {
+        let program = build_owned_program(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+    gl_Position = vec4(0.0);
+}
+        "#,
+            r#"
+#version 450
+
+layout(location = 0) in vec4 color;
+layout(location = 0) out vec4 result;
+
+void main() {
+    result = color;
+}
+        "#,
+        );
+
+        program.compile(ShaderStage::Vertex).expect("compile");
+        program.compile(ShaderStage::Fragment).expect("compile");
+
+        let stages: Vec<ShaderStage> = program.shaders().iter().map(|s| s.stage()).collect();
+        assert_eq!(stages, [ShaderStage::Vertex, ShaderStage::Fragment]);
+
+        let shaders = program.into_shaders();
+        assert_eq!(shaders.len(), 2);
+
+        // The shaders are no longer owned by the program, so they can be reused.
+        let compiler = Compiler::acquire().unwrap();
+        let mut program = Program::new(compiler);
+        for shader in &shaders {
+            program
+                .add_owned_shader(Arc::clone(shader))
+                .expect("add shader");
+        }
+        let program = program.link().expect("link");
+        program.compile(ShaderStage::Fragment).expect("compile");
+        drop(program);
+
+        for shader in shaders {
+            Arc::try_unwrap(shader).ok().expect("unique shader");
+        }
+    }
+
+    fn count_instructions(code: &[u32], op: rspirv::spirv::Op) -> usize {
+        let mut count = 0;
+        let mut index = 5;
+        while index < code.len() {
+            if code[index] & 0xffff == op as u32 {
+                count += 1;
+            }
+            index += (code[index] >> 16) as usize;
+        }
+        count
+    }
+
+    #[test]
+    pub fn test_compile_with_options() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    vec4 named_value = vec4(1.0);
+    color = named_value;
+}
+        "#,
+        );
+
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions {
+                messages: ShaderMessage::DEBUG_INFO,
+                ..CompilerOptions::default()
+            },
+            None,
+            None,
+        )
+        .expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+
+        let mut program = Program::new(compiler);
+        program.add_shader(&shader).expect("add shader");
+        let program = program.link().expect("link");
+
+        let code = program
+            .compile_with_options(ShaderStage::Fragment, &SpirvOptions::default())
+            .expect("compile");
+        assert_eq!(count_instructions(&code, rspirv::spirv::Op::Line), 0);
+        assert!(count_instructions(&code, rspirv::spirv::Op::Name) > 0);
+
+        let code = program
+            .compile_with_options(
+                ShaderStage::Fragment,
+                &SpirvOptions {
+                    generate_debug_info: true,
+                    ..SpirvOptions::default()
+                },
+            )
+            .expect("compile");
+        assert!(count_instructions(&code, rspirv::spirv::Op::Line) > 0);
+        assert!(count_instructions(&code, rspirv::spirv::Op::Name) > 0);
+
+        let code = program
+            .compile_with_options(
+                ShaderStage::Fragment,
+                &SpirvOptions {
+                    strip_debug_info: true,
+                    ..SpirvOptions::default()
+                },
+            )
+            .expect("compile");
+        assert_eq!(count_instructions(&code, rspirv::spirv::Op::Line), 0);
+        assert_eq!(count_instructions(&code, rspirv::spirv::Op::Name), 0);
+    }
+
+    #[test]
+    pub fn test_compile_optimized() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+vec4 unused(vec4 value) {
+    return value * 2.0;
+}
+
+void main() {
+    vec4 value = vec4(1.0);
+    for (int i = 0; i < 4; i++) {
+        value *= 0.5;
+    }
+    color = value;
+}
+        "#,
+        );
+
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            None,
+            None,
+        )
+        .expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+
+        let mut program = Program::new(compiler);
+        program.add_shader(&shader).expect("add shader");
+        let program = program.link().expect("link");
+
+        let unoptimized = program
+            .compile_with_options(ShaderStage::Fragment, &SpirvOptions::default())
+            .expect("compile");
+
+        for optimization_level in [OptimizationLevel::Performance, OptimizationLevel::Size] {
+            let options = SpirvOptions {
+                optimization_level,
+                ..SpirvOptions::default()
+            };
+            let result = program.compile_with_options(ShaderStage::Fragment, &options);
+
+            if cfg!(feature = "optimizer") {
+                let optimized = result.expect("compile");
+                assert!(optimized.len() < unoptimized.len());
+            } else {
+                assert!(matches!(result, Err(GlslangError::OptimizerUnavailable)));
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_compile_validated() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            None,
+            None,
+        )
+        .expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+
+        let mut program = Program::new(compiler);
+        program.add_shader(&shader).expect("add shader");
+        let program = program.link().expect("link");
+
+        let result = program.compile_with_options(
+            ShaderStage::Fragment,
+            &SpirvOptions {
+                validate: true,
+                ..SpirvOptions::default()
+            },
+        );
+
+        if cfg!(feature = "spirv-validation") {
+            result.expect("valid SPIR-V");
+        } else {
+            assert!(matches!(
+                result,
+                Err(GlslangError::SpirvValidatorUnavailable)
+            ));
+        }
+    }
+
+    #[test]
+    pub fn test_compile_validated_optimized_and_stripped() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    vec4 value = vec4(1.0);
+    for (int i = 0; i < 4; i++) {
+        value *= 0.5;
+    }
+    color = value;
+}
+        "#,
+        );
+
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            None,
+            None,
+        )
+        .expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+
+        let mut program = Program::new(compiler);
+        program.add_shader(&shader).expect("add shader");
+        let program = program.link().expect("link");
+
+        for optimization_level in [
+            OptimizationLevel::None,
+            OptimizationLevel::Performance,
+            OptimizationLevel::Size,
+        ] {
+            let options = SpirvOptions {
+                generate_debug_info: true,
+                strip_debug_info: true,
+                optimization_level,
+                validate: true,
+                ..SpirvOptions::default()
+            };
+            let result = program.compile_with_options(ShaderStage::Fragment, &options);
+
+            if !cfg!(feature = "optimizer") && optimization_level != OptimizationLevel::None {
+                assert!(matches!(result, Err(GlslangError::OptimizerUnavailable)));
+            } else if !cfg!(feature = "spirv-validation") {
+                assert!(matches!(
+                    result,
+                    Err(GlslangError::SpirvValidatorUnavailable)
+                ));
+            } else {
+                result.expect("valid SPIR-V");
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_add_macros() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::try_from(String::from(
+            r#"
+#version 460
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(CUSTOM_MACRO);
+}
+        "#,
+        ))
+        .expect("source");
+
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Vertex,
+            &CompilerOptions {
+                source_language: SourceLanguage::GLSL,
+                target: Target::None(None),
+                messages: ShaderMessage::DEBUG_INFO | ShaderMessage::DEFAULT,
+                version_profile: None,
+                default_version_profile: None,
+                forward_compatible: false,
+                hlsl: None,
+            },
+            Some(&[("CUSTOM_MACRO", Some("1.0"))]),
+            None,
+        )
+        .expect("target");
+        let _shader = Shader::new(&compiler, input).expect("shader init");
+    }
+
+    #[test]
+    pub fn test_include_handler() {
+        let compiler = Compiler::acquire().unwrap();
+
+        struct MyIncludeHandler {
+            header_included: Vec<String>,
+        }
+        impl IncludeHandler for MyIncludeHandler {
+            fn include(
+                &mut self,
+                _ty: crate::include::IncludeType,
+                header_name: &str,
+                _includer_name: &str,
+                _include_depth: usize,
+            ) -> Option<IncludeResult> {
+                self.header_included.push(header_name.into());
+                Some(IncludeResult {
+                    name: "included_macro".into(),
+                    data: "#define INCLUDED_MACRO 0.0".into(),
+                })
+            }
+        }
+
+        let source = ShaderSource::try_from(String::from(
+            r#"
+#version 460
+#extension GL_GOOGLE_include_directive : require
+#include "custom_include.glsl"
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(INCLUDED_MACRO);
+}
+        "#,
+        ))
+        .expect("source");
+        let mut include_handler = MyIncludeHandler {
+            header_included: vec![],
+        };
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Vertex,
+            &CompilerOptions {
+                source_language: SourceLanguage::GLSL,
+                target: Target::OpenGL {
+                    version: OpenGlVersion::OpenGL4_5,
+                    spirv_version: None,
+                },
+                messages: ShaderMessage::DEBUG_INFO | ShaderMessage::DEFAULT,
+                version_profile: None,
+                default_version_profile: None,
+                forward_compatible: false,
+                hlsl: None,
+            },
+            Some(&[("CUSTOM_MACRO", Some("1.0"))]),
             Some(&mut include_handler),
         )
         .expect("target");
@@ -518,4 +1368,256 @@ void main() {
             ""
         );
     }
+
+    fn entry_point_names(code: &[u32]) -> Vec<String> {
+        let mut loader = rspirv::dr::Loader::new();
+        rspirv::binary::parse_words(code, &mut loader).unwrap();
+        let module = loader.module();
+
+        module
+            .entry_points
+            .iter()
+            .map(|entry_point| match &entry_point.operands[2] {
+                rspirv::dr::Operand::LiteralString(name) => name.clone(),
+                operand => panic!("unexpected entry point operand {operand:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn test_hlsl_entry_point() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+float4 VSMain(float4 position : POSITION) : SV_POSITION
+{
+    return position;
+}
+
+float4 PSMain() : SV_TARGET
+{
+    return float4(1.0, 0.0, 0.0, 1.0);
+}
+"#,
+        );
+
+        let options = CompilerOptions {
+            source_language: SourceLanguage::HLSL,
+            target: Target::Vulkan {
+                version: VulkanVersion::Vulkan1_0,
+                spirv_version: SpirvVersion::SPIRV1_0,
+            },
+            messages: ShaderMessage::DEFAULT,
+            version_profile: None,
+            default_version_profile: None,
+            forward_compatible: false,
+            hlsl: None,
+        };
+
+        let mut input =
+            ShaderInput::new(&source, ShaderStage::Vertex, &options, None, None).expect("target");
+        input.set_entry_point("VSMain");
+        let vertex = Shader::new(compiler, input).expect("shader init");
+        let code = vertex.compile().expect("compile");
+        assert_eq!(entry_point_names(&code), vec!["VSMain"]);
+
+        let mut input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        input.set_entry_point("main");
+        input.set_source_entry_point("PSMain");
+        let fragment = Shader::new(compiler, input).expect("shader init");
+        let code = fragment.compile().expect("compile");
+        assert_eq!(entry_point_names(&code), vec!["main"]);
+    }
+
+    fn descriptor_sets(code: &[u32]) -> Vec<(String, u32, u32)> {
+        let mut loader = rspirv::dr::Loader::new();
+        rspirv::binary::parse_words(code, &mut loader).unwrap();
+        let module = loader.module();
+
+        let name_of = |id: u32| {
+            module
+                .debug_names
+                .iter()
+                .find(|name| name.operands[0].unwrap_id_ref() == id)
+                .map(|name| name.operands[1].unwrap_literal_string().to_string())
+                .unwrap_or_default()
+        };
+
+        let decoration_of = |id: u32, decoration: rspirv::spirv::Decoration| {
+            module
+                .annotations
+                .iter()
+                .find(|annotation| {
+                    annotation.class.opcode == rspirv::spirv::Op::Decorate
+                        && annotation.operands[0].unwrap_id_ref() == id
+                        && annotation.operands[1].unwrap_decoration() == decoration
+                })
+                .map(|annotation| annotation.operands[2].unwrap_literal_bit32())
+        };
+
+        let mut bindings: Vec<(String, u32, u32)> = module
+            .annotations
+            .iter()
+            .filter(|annotation| {
+                annotation.class.opcode == rspirv::spirv::Op::Decorate
+                    && annotation.operands[1].unwrap_decoration()
+                        == rspirv::spirv::Decoration::DescriptorSet
+            })
+            .map(|annotation| {
+                let id = annotation.operands[0].unwrap_id_ref();
+                (
+                    name_of(id),
+                    annotation.operands[2].unwrap_literal_bit32(),
+                    decoration_of(id, rspirv::spirv::Decoration::Binding).unwrap_or_default(),
+                )
+            })
+            .collect();
+        bindings.sort();
+        bindings
+    }
+
+    #[test]
+    pub fn test_hlsl_resource_set_binding() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+Texture2D tex : register(t0);
+SamplerState samp : register(s1);
+
+float4 main(float2 uv : TEXCOORD0) : SV_TARGET
+{
+    return tex.Sample(samp, uv);
+}
+"#,
+        );
+
+        let options = CompilerOptions {
+            source_language: SourceLanguage::HLSL,
+            target: Target::Vulkan {
+                version: VulkanVersion::Vulkan1_0,
+                spirv_version: SpirvVersion::SPIRV1_0,
+            },
+            messages: ShaderMessage::DEFAULT,
+            version_profile: None,
+            default_version_profile: None,
+            forward_compatible: false,
+            hlsl: None,
+        };
+
+        let mut input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        input
+            .set_resource_set_binding(HlslResourceSetBinding::Global(2))
+            .expect("binding");
+        let shader = Shader::new(compiler, input).expect("shader init");
+        let code = shader.compile().expect("compile");
+        assert_eq!(
+            descriptor_sets(&code),
+            vec![("samp".to_string(), 2, 1), ("tex".to_string(), 2, 0)]
+        );
+
+        let mut input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        input
+            .set_resource_set_binding(HlslResourceSetBinding::PerRegister(vec![
+                HlslRegisterBinding {
+                    register: "t0".to_string(),
+                    set: 1,
+                    binding: 5,
+                },
+            ]))
+            .expect("binding");
+        let shader = Shader::new(compiler, input).expect("shader init");
+        let code = shader.compile().expect("compile");
+        assert_eq!(
+            descriptor_sets(&code),
+            vec![("samp".to_string(), 0, 1), ("tex".to_string(), 1, 5)]
+        );
+
+        let mut input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        for register in ["", "t", "0", "t0\0", "t 0"] {
+            assert!(matches!(
+                input.set_resource_set_binding(HlslResourceSetBinding::PerRegister(vec![
+                    HlslRegisterBinding {
+                        register: register.to_string(),
+                        set: 1,
+                        binding: 5,
+                    },
+                ])),
+                Err(GlslangError::InvalidHlslRegister(r)) if r == register
+            ));
+        }
+    }
+
+    #[test]
+    pub fn test_hlsl_options() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+half4 main(half4 color : COLOR) : SV_TARGET
+{
+    return color * half(0.5);
+}
+"#,
+        );
+
+        let options = CompilerOptions {
+            source_language: SourceLanguage::HLSL,
+            hlsl: Some(HlslOptions {
+                enable_16bit_types: true,
+                ..HlslOptions::default()
+            }),
+            ..CompilerOptions::default()
+        };
+
+        let input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+        let code = shader.compile().expect("compile");
+
+        let mut loader = rspirv::dr::Loader::new();
+        rspirv::binary::parse_words(&code, &mut loader).unwrap();
+        let module = loader.module();
+        assert!(module.capabilities.iter().any(|capability| {
+            capability.operands[0].unwrap_capability() == rspirv::spirv::Capability::Float16
+        }));
+    }
+
+    #[test]
+    pub fn test_hlsl_options_require_hlsl() {
+        let source = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+"#,
+        );
+
+        let options = CompilerOptions {
+            hlsl: Some(HlslOptions::default()),
+            ..CompilerOptions::default()
+        };
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None),
+            Err(GlslangError::UnexpectedHlslOptions(SourceLanguage::GLSL))
+        ));
+
+        let options = CompilerOptions {
+            messages: ShaderMessage::HLSL_OFFSETS,
+            ..CompilerOptions::default()
+        };
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None),
+            Err(GlslangError::UnexpectedHlslOptions(SourceLanguage::GLSL))
+        ));
+    }
 }
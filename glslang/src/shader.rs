@@ -1,29 +1,96 @@
 use crate::ctypes::{ResourceType, ShaderOptions, ShaderStage};
-use crate::error::{GlslangError, GlslangErrorLog};
-use crate::error::GlslangError::ParseError;
+use crate::error::{GlslangError, GlslangErrorLog, TargetError};
+use crate::error::GlslangError::{ParseError, PreprocessError};
 use crate::include::IncludeHandler;
+use crate::spirv::SpirvModule;
 use crate::{include, limits, limits::ResourceLimits, Compiler};
-use crate::{GlslProfile, SourceLanguage, SpirvVersion};
+use crate::{GlslProfile, HlslProfile, SourceLanguage, SpirvVersion};
 use bitflags::bitflags;
 use glslang_sys as sys;
 use glslang_sys::glsl_include_callbacks_s;
-use rustc_hash::FxHashMap;
-use smartstring::{LazyCompact, SmartString};
 use std::borrow::Cow;
 use std::ffi::{c_void, CStr, CString};
+use std::fmt::{Display, Formatter};
 use std::ptr::NonNull;
+use std::str::FromStr;
 
 /// A handle to a shader in the glslang compiler.
 pub struct Shader<'a> {
     pub(crate) handle: NonNull<sys::glslang_shader_t>,
     pub(crate) stage: ShaderStage,
     pub(crate) is_spirv: bool,
+    pub(crate) target: Target,
+    pub(crate) link_messages: ShaderMessage,
     _compiler: &'a Compiler,
+    // glslang keeps a pointer to the preamble instead of copying it.
+    _preamble: CString,
 }
 
 impl<'a> Shader<'a> {
     /// Create a new shader instance with the provided [`ShaderInput`](crate::ShaderInput).
     pub fn new(_compiler: &'a Compiler, input: ShaderInput) -> Result<Self, GlslangError> {
+        let shader = Self::create(_compiler, &input);
+
+        unsafe {
+            // Preprocessing first keeps the preprocessed code available to get_preprocessed_code.
+            if sys::glslang_shader_preprocess(shader.handle.as_ptr(), &input.input) == 0 {
+                return Err(ParseError(GlslangErrorLog::new(
+                    shader.get_log(),
+                    shader.get_debug_log(),
+                )));
+            }
+
+            // The source is parsed again with the preamble instead of the preprocessed code,
+            // where glslang writes the preamble in front of the #version directive.
+            if sys::glslang_rs_shader_parse(shader.handle.as_ptr(), &input.input) == 0 {
+                return Err(ParseError(GlslangErrorLog::new(
+                    shader.get_log(),
+                    shader.get_debug_log(),
+                )));
+            }
+        }
+        Ok(shader)
+    }
+
+    /// Run only the preprocessor on the provided [`ShaderInput`](crate::ShaderInput), returning the
+    /// preprocessed source.
+    ///
+    /// The shader is not parsed, so the preprocessed source is returned even if the shader
+    /// would fail semantic checks.
+    pub fn preprocess(_compiler: &'a Compiler, input: ShaderInput) -> Result<String, GlslangError> {
+        let shader = Self::create(_compiler, &input);
+
+        let mut preprocess_input = input.input;
+        preprocess_input.messages |= sys::glslang_messages_t::ONLY_PREPROCESSOR;
+
+        unsafe {
+            if sys::glslang_shader_preprocess(shader.handle.as_ptr(), &preprocess_input) == 0 {
+                return Err(PreprocessError(GlslangErrorLog::new(
+                    shader.get_log(),
+                    shader.get_debug_log(),
+                )));
+            }
+        }
+
+        Ok(shader.get_preprocessed_code())
+    }
+
+    /// Create the shader handle and apply the settings of the input, without preprocessing or parsing.
+    fn create(_compiler: &'a Compiler, input: &ShaderInput) -> Self {
+        let mut preamble = input
+            .defines
+            .iter()
+            .map(MacroDefine::directive)
+            .collect::<Vec<String>>()
+            .join("");
+
+        if let Some(text) = &input.preamble {
+            preamble.push_str(text);
+            if !text.ends_with('\n') {
+                preamble.push('\n');
+            }
+        }
+
         let shader = Self {
             handle: unsafe {
                 NonNull::new(sys::glslang_shader_create(&input.input))
@@ -31,33 +98,68 @@ impl<'a> Shader<'a> {
             },
             stage: input.input.stage,
             is_spirv: input.input.target_language == sys::glslang_target_language_t::SPIRV,
+            target: input.target.clone(),
+            link_messages: input.link_messages(),
             _compiler,
+            _preamble: CString::new(preamble).expect("Invalid preamble format"),
         };
 
-        let preamble = input
-            .defines
-            .iter()
-            .map(|(k, v)| format!("#define {} {}\n", k, v.clone().unwrap_or_default()))
-            .collect::<Vec<String>>()
-            .join("");
-
-        let cpreamble = CString::new(preamble).expect("Invalid preamble format");
         unsafe {
-            sys::glslang_shader_set_preamble(shader.handle.as_ptr(), cpreamble.as_ptr());
+            sys::glslang_shader_set_preamble(shader.handle.as_ptr(), shader._preamble.as_ptr());
         }
 
-        unsafe {
-            if sys::glslang_shader_preprocess(shader.handle.as_ptr(), &input.input) == 0 {
-                return Err(ParseError(GlslangErrorLog::new(shader.get_log(), shader.get_debug_log())));
+        if let Some(entry_point) = &input.entry_point {
+            unsafe {
+                sys::glslang_rs_shader_set_entry_point(
+                    shader.handle.as_ptr(),
+                    entry_point.as_ptr(),
+                );
             }
         }
 
-        unsafe {
-            if sys::glslang_shader_parse(shader.handle.as_ptr(), &input.input) == 0 {
-                return Err(ParseError(GlslangErrorLog::new(shader.get_log(), shader.get_debug_log())));
+        if let Some(resource_set_binding) = &input.resource_set_binding {
+            // The registers were validated by set_resource_set_binding.
+            let bindings = resource_set_binding.to_strings();
+            let bindings = bindings
+                .into_iter()
+                .map(|s| CString::new(s).expect("Invalid resource set binding"))
+                .collect::<Vec<CString>>();
+            let binding_ptrs = bindings.iter().map(|s| s.as_ptr()).collect::<Vec<_>>();
+
+            // glslang copies the strings, so they do not need to outlive this call.
+            unsafe {
+                sys::glslang_shader_set_resource_set_binding(
+                    shader.handle.as_ptr(),
+                    binding_ptrs.as_ptr(),
+                    binding_ptrs.len() as u32,
+                );
             }
         }
-        Ok(shader)
+
+        if let Some(hlsl_options) = &input.hlsl_options {
+            unsafe {
+                sys::glslang_rs_shader_set_hlsl_io_mapping(
+                    shader.handle.as_ptr(),
+                    hlsl_options.io_mapping,
+                );
+                sys::glslang_rs_shader_set_flatten_uniform_arrays(
+                    shader.handle.as_ptr(),
+                    hlsl_options.flatten_uniform_arrays,
+                );
+                sys::glslang_rs_shader_set_invert_y(shader.handle.as_ptr(), hlsl_options.invert_y);
+            }
+        }
+
+        if let Some(source_entry_point) = &input.source_entry_point {
+            unsafe {
+                sys::glslang_rs_shader_set_source_entry_point(
+                    shader.handle.as_ptr(),
+                    source_entry_point.as_ptr(),
+                );
+            }
+        }
+
+        shader
     }
 
     /// Set shader options flags.
@@ -98,6 +200,11 @@ impl<'a> Shader<'a> {
         unsafe { sys::glslang_shader_set_glsl_version(self.handle.as_ptr(), version) }
     }
 
+    /// The stage of the shader.
+    pub fn stage(&self) -> ShaderStage {
+        self.stage
+    }
+
     pub fn get_log(&self) -> String {
         let c_str =
             unsafe { CStr::from_ptr(sys::glslang_shader_get_info_log(self.handle.as_ptr())) };
@@ -121,23 +228,27 @@ impl<'a> Shader<'a> {
     }
 
     /// Convenience method to compile this shader without linking to other shaders.
-    pub fn compile(&self) -> Result<Vec<u32>, GlslangError> {
+    pub fn compile(&self) -> Result<SpirvModule, GlslangError> {
         let mut program = self._compiler.create_program();
-        program.add_shader(&self);
+        program.add_shader(self)?;
         program.compile(self.stage)
     }
 
     /// Convenience method to compile this shader without linking to other shaders, optimizing for size.
-    pub fn compile_size_optimized(&self) -> Result<Vec<u32>, GlslangError> {
+    pub fn compile_size_optimized(&self) -> Result<SpirvModule, GlslangError> {
         let mut program = self._compiler.create_program();
-        program.add_shader(&self);
+        program.add_shader(self)?;
         program.compile_size_optimized(self.stage)
     }
 
     /// Get the preprocessed shader string.
+    ///
+    /// Any `#version` directive is on the first line, so the code can be parsed again
+    /// with [`ShaderInput::from_preprocessed`].
     pub fn get_preprocessed_code(&self) -> String {
         let c_str = unsafe {
-            // SAFETY: for Shader to be initialized preprocessing + parsing had to be complete.
+            // SAFETY: preprocessing has to be complete before the shader is handed out,
+            // and glslang always provides a valid (possibly empty) string.
             CStr::from_ptr(sys::glslang_shader_get_preprocessed_code(
                 self.handle.as_ptr(),
             ))
@@ -147,8 +258,36 @@ impl<'a> Shader<'a> {
             .into_string()
             .expect("Expected glslang info log to be valid UTF-8");
 
-        string
+        hoist_version_directive(string)
+    }
+}
+
+/// Whether the line is a `#version` directive.
+fn is_version_directive(line: &str) -> bool {
+    line.trim_start()
+        .strip_prefix('#')
+        .is_some_and(|directive| directive.trim_start().starts_with("version"))
+}
+
+/// Move the `#version` directive to the first line of preprocessed code.
+///
+/// glslang writes the output of the preamble in front of the directive, which is an error
+/// when the code is parsed again. The number of lines before the rest of the source is kept.
+fn hoist_version_directive(code: String) -> String {
+    let mut lines = code.split_inclusive('\n').collect::<Vec<_>>();
+    let Some(index) = lines.iter().position(|line| is_version_directive(line)) else {
+        return code;
+    };
+
+    if index == 0 {
+        return code;
     }
+
+    let version = lines.remove(index);
+    let mut hoisted = version.trim_end_matches(['\r', '\n']).to_string();
+    hoisted.push('\n');
+    hoisted.extend(lines);
+    hoisted
 }
 
 impl<'a> Drop for Shader<'a> {
@@ -195,6 +334,789 @@ void main() {
 
         println!("{}", code);
     }
+
+    #[test]
+    pub fn test_preprocess_only() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = undeclared_function(vec4(CUSTOM_MACRO));
+}
+        "#,
+        );
+
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            Some(&[("CUSTOM_MACRO", Some("1.0"))]),
+            None,
+        )
+        .expect("target");
+        let code = compiler.preprocess(input).expect("preprocess");
+        assert!(code.contains("undeclared_function(vec4(1.0))"));
+    }
+
+    #[test]
+    pub fn test_preprocess_error() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+#error custom preprocessor error
+
+void main() {}
+        "#,
+        );
+
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            None,
+            None,
+        )
+        .expect("target");
+        assert!(matches!(
+            compiler.preprocess(input),
+            Err(GlslangError::PreprocessError(_))
+        ));
+
+        // Creating a shader reports preprocessor errors as parse errors.
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            None,
+            None,
+        )
+        .expect("target");
+        assert!(matches!(
+            Shader::new(compiler, input),
+            Err(GlslangError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_compile_preprocessed() {
+        let compiler = Compiler::acquire().unwrap();
+
+        struct MyIncludeHandler;
+        impl IncludeHandler for MyIncludeHandler {
+            fn include(
+                &mut self,
+                _ty: crate::include::IncludeType,
+                _header_name: &str,
+                _includer_name: &str,
+                _include_depth: usize,
+            ) -> Option<crate::include::IncludeResult> {
+                Some(crate::include::IncludeResult {
+                    name: "included_macro".into(),
+                    data: "#define INCLUDED_MACRO 0.0".into(),
+                })
+            }
+        }
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+#extension GL_GOOGLE_include_directive : require
+#include "custom_include.glsl"
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(INCLUDED_MACRO, CUSTOM_MACRO, 0.0, 1.0);
+}
+        "#,
+        );
+
+        let mut include_handler = MyIncludeHandler;
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            Some(&[("CUSTOM_MACRO", Some("1.0"))]),
+            Some(&mut include_handler),
+        )
+        .expect("target");
+        let preprocessed = ShaderSource::from(compiler.preprocess(input).expect("preprocess"));
+
+        let input = ShaderInput::from_preprocessed(
+            &preprocessed,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+        )
+        .expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+        shader.compile().expect("compile");
+    }
+
+    #[test]
+    pub fn test_macro_defines() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+#ifdef REMOVED
+    color = undeclared_function();
+#else
+    color = vec4(SCALE(FIRST, SECOND));
+#endif
+}
+        "#,
+        );
+
+        let mut input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            Some(&[
+                ("FIRST", Some("0.25")),
+                ("SECOND", Some("FIRST * 2.0")),
+                ("SCALE(a, b)", Some("((a) * (b))")),
+                ("REMOVED", None),
+            ]),
+            None,
+        )
+        .expect("target");
+        input
+            .add_define(MacroDefine::undef_from_str("REMOVED"))
+            .expect("undef");
+
+        let preamble = input
+            .defines
+            .iter()
+            .map(MacroDefine::directive)
+            .collect::<String>();
+        assert_eq!(
+            preamble,
+            "#define FIRST 0.25\n#define SECOND FIRST * 2.0\n#define SCALE(a, b) ((a) * (b))\n#define REMOVED\n#undef REMOVED\n"
+        );
+
+        let shader = Shader::new(compiler, input).expect("shader init");
+        shader.compile().expect("compile");
+    }
+
+    #[test]
+    pub fn test_invalid_macro_defines() {
+        let source = ShaderSource::from("#version 450\nvoid main() {}\n");
+
+        let invalid: &[(&str, Option<&str>)] = &[
+            ("1ABC", None),
+            ("", None),
+            ("A B", None),
+            ("INJECTED", Some("1.0\n#version 100")),
+            ("NUL", Some("1.0\0")),
+            ("CONTINUED", Some("1.0 \\")),
+            ("COMMENTED", Some("1.0 /* comment")),
+            ("COMMENTED", Some("1.0 /* comment */ 2.0 /*")),
+            ("FUNC(a,", None),
+            ("FUNC(a, a)", None),
+            ("FUNC(1)", None),
+        ];
+
+        for define in invalid {
+            assert!(matches!(
+                ShaderInput::new(
+                    &source,
+                    ShaderStage::Fragment,
+                    &CompilerOptions::default(),
+                    Some(std::slice::from_ref(define)),
+                    None,
+                ),
+                Err(GlslangError::InvalidMacroDefine(_))
+            ));
+        }
+
+        assert!(matches!(
+            ShaderInput::new(
+                &source,
+                ShaderStage::Fragment,
+                &CompilerOptions::default(),
+                Some(&[("DUPLICATE", Some("1")), ("DUPLICATE(a)", Some("a"))]),
+                None,
+            ),
+            Err(GlslangError::DuplicateMacroDefine(name)) if name == "DUPLICATE"
+        ));
+    }
+
+    #[test]
+    pub fn test_parse_macro_define() {
+        let strings = ["NAME", "EMPTY=", "VALUE=1.0", "EQ=a=b", "F(x)=x*2"];
+        let defines = MacroDefine::parse_all(strings).expect("defines");
+
+        assert_eq!(
+            defines,
+            vec![
+                MacroDefine::new_from_str("NAME", None),
+                MacroDefine::new_from_str("EMPTY", Some("")),
+                MacroDefine::new_from_str("VALUE", Some("1.0")),
+                MacroDefine::new_from_str("EQ", Some("a=b")),
+                MacroDefine::new_from_str("F(x)", Some("x*2")),
+            ]
+        );
+
+        for (define, string) in defines.iter().zip(strings) {
+            assert_eq!(define.to_string(), string);
+            assert_eq!(&string.parse::<MacroDefine>().expect("define"), define);
+        }
+
+        assert!(matches!(
+            "=1".parse::<MacroDefine>(),
+            Err(GlslangError::InvalidMacroDefine(_))
+        ));
+        assert!(matches!(
+            MacroDefine::parse_all(["VALID", "NOT VALID"]),
+            Err(GlslangError::InvalidMacroDefine(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_preamble() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = helper(vec4(CUSTOM_MACRO));
+}
+        "#,
+        );
+
+        let mut input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            Some(&[("CUSTOM_MACRO", Some("1.0"))]),
+            None,
+        )
+        .expect("target");
+        input.set_preamble(
+            "#extension GL_EXT_control_flow_attributes : require\nvec4 helper(vec4 v) { return v * CUSTOM_MACRO; }",
+        );
+        let shader = Shader::new(compiler, input).expect("shader init");
+        shader.compile().expect("compile");
+
+        // The preprocessed code starts with the #version directive, so it can be parsed again.
+        let mut input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            Some(&[("CUSTOM_MACRO", Some("1.0"))]),
+            None,
+        )
+        .expect("target");
+        input.set_preamble("vec4 helper(vec4 v) { return v * CUSTOM_MACRO; }");
+        let code = compiler.preprocess(input).expect("preprocess");
+        assert!(code.starts_with("#version 450\n"));
+
+        let preprocessed = ShaderSource::from(code);
+        let input = ShaderInput::from_preprocessed(
+            &preprocessed,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+        )
+        .expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+        shader.compile().expect("compile");
+    }
+
+    #[test]
+    pub fn test_hoist_version_directive() {
+        assert_eq!(
+            hoist_version_directive("\nvec4 helper();\n#version 450\nvoid main() {}\n".into()),
+            "#version 450\n\nvec4 helper();\nvoid main() {}\n"
+        );
+        assert_eq!(
+            hoist_version_directive("#version 450\nvoid main() {}\n".into()),
+            "#version 450\nvoid main() {}\n"
+        );
+        assert_eq!(
+            hoist_version_directive("void main() {}\n".into()),
+            "void main() {}\n"
+        );
+    }
+
+    #[test]
+    pub fn test_default_version_profile() {
+        let compiler = Compiler::acquire().unwrap();
+
+        // GLSL ES 1.00 requires a default precision for floats in fragment shaders.
+        let source = ShaderSource::from(
+            r#"
+void main() {
+    float x = 1.0;
+    gl_FragColor = vec4(x);
+}
+        "#,
+        );
+
+        let options = CompilerOptions {
+            target: Target::None(None),
+            ..CompilerOptions::default()
+        };
+        let input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        assert!(matches!(
+            Shader::new(compiler, input),
+            Err(GlslangError::ParseError(_))
+        ));
+
+        let options = CompilerOptions {
+            target: Target::None(None),
+            default_version_profile: Some((110, GlslProfile::None)),
+            ..CompilerOptions::default()
+        };
+        let input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        Shader::new(compiler, input).expect("shader init");
+
+        // GLSL ES 3.00 requires the ES profile, which glslang only applies if it is forced.
+        let source = ShaderSource::from(
+            r#"
+precision mediump float;
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        let options = CompilerOptions {
+            target: Target::OpenGlEs {
+                version: OpenGlEsVersion::OpenGLES3_0,
+            },
+            default_version_profile: Some((300, GlslProfile::ES)),
+            ..CompilerOptions::default()
+        };
+        let input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        Shader::new(compiler, input).expect("shader init");
+
+        // A #version directive in the source is not overridden by the default.
+        let source = ShaderSource::from(
+            r#"
+// A comment before the version directive.
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        let options = CompilerOptions {
+            target: Target::None(None),
+            default_version_profile: Some((300, GlslProfile::ES)),
+            ..CompilerOptions::default()
+        };
+        let input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        Shader::new(compiler, input).expect("shader init");
+    }
+
+    #[test]
+    pub fn test_forward_compatible() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 130
+
+attribute vec4 position;
+
+void main() {
+    gl_Position = position;
+}
+        "#,
+        );
+
+        let options = CompilerOptions {
+            target: Target::None(None),
+            ..CompilerOptions::default()
+        };
+        let input =
+            ShaderInput::new(&source, ShaderStage::Vertex, &options, None, None).expect("target");
+        Shader::new(compiler, input).expect("shader init");
+
+        let options = CompilerOptions {
+            target: Target::None(None),
+            forward_compatible: true,
+            ..CompilerOptions::default()
+        };
+        let input =
+            ShaderInput::new(&source, ShaderStage::Vertex, &options, None, None).expect("target");
+        assert!(matches!(
+            Shader::new(compiler, input),
+            Err(GlslangError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_vulkan_target() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        let target = Target::vulkan(VulkanVersion::Vulkan1_3);
+        assert!(matches!(
+            target,
+            Target::Vulkan {
+                version: VulkanVersion::Vulkan1_3,
+                spirv_version: SpirvVersion::SPIRV1_6,
+            }
+        ));
+
+        let options = CompilerOptions {
+            target,
+            ..CompilerOptions::default()
+        };
+        let input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+        shader.compile().expect("compile");
+
+        let options = CompilerOptions {
+            target: Target::Vulkan {
+                version: VulkanVersion::Vulkan1_0,
+                spirv_version: SpirvVersion::SPIRV1_5,
+            },
+            ..CompilerOptions::default()
+        };
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None),
+            Err(GlslangError::InvalidTarget(_, _))
+        ));
+    }
+
+    #[test]
+    pub fn test_invalid_targets() {
+        let source = ShaderSource::from("#version 310 es\nvoid main() {}");
+
+        let options = CompilerOptions {
+            target: Target::OpenGL {
+                version: OpenGlVersion::OpenGL4_5,
+                spirv_version: Some(SpirvVersion::SPIRV1_3),
+            },
+            ..CompilerOptions::default()
+        };
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Vertex, &options, None, None),
+            Err(GlslangError::InvalidTarget(
+                _,
+                TargetError::UnsupportedSpirvVersion {
+                    spirv_version: SpirvVersion::SPIRV1_3,
+                    max_spirv_version: SpirvVersion::SPIRV1_0,
+                }
+            ))
+        ));
+
+        let options = CompilerOptions {
+            source_language: SourceLanguage::HLSL,
+            target: Target::OpenGL {
+                version: OpenGlVersion::OpenGL4_5,
+                spirv_version: None,
+            },
+            ..CompilerOptions::default()
+        };
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Vertex, &options, None, None),
+            Err(GlslangError::InvalidTarget(_, TargetError::HlslForOpenGl))
+        ));
+
+        let options = CompilerOptions {
+            target: Target::vulkan(VulkanVersion::Vulkan1_1),
+            ..CompilerOptions::default()
+        };
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Mesh, &options, None, None),
+            Err(GlslangError::InvalidTarget(
+                _,
+                TargetError::UnsupportedStage(ShaderStage::Mesh)
+            ))
+        ));
+        ShaderInput::new(&source, ShaderStage::Vertex, &options, None, None).expect("target");
+
+        let source = ShaderSource::from("#version 300 es\nvoid main() {}");
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Vertex, &options, None, None),
+            Err(GlslangError::InvalidProfile(_, 300, GlslProfile::ES))
+        ));
+    }
+
+    #[test]
+    pub fn test_parse_profile() {
+        let profiles = [
+            (
+                "#version 450\nvoid main() {}",
+                Some((450, GlslProfile::None)),
+            ),
+            ("  #  version 310 es  \n", Some((310, GlslProfile::ES))),
+            (
+                "\n// #version 100\n#version 410 core // desktop\n",
+                Some((410, GlslProfile::Core)),
+            ),
+            (
+                "/* #version 100\n*/\n#version 150 compatibility\n",
+                Some((150, GlslProfile::Compatibility)),
+            ),
+            (
+                "#version 300 es /* comment */\n",
+                Some((300, GlslProfile::ES)),
+            ),
+            ("#versionx 450\n", None),
+            ("#version\n", None),
+            ("#version 450 core extra\n", None),
+            ("#version es\n", None),
+            ("void main() {}\n", None),
+        ];
+
+        for (source, profile) in profiles {
+            assert_eq!(
+                ShaderSource::from(source).parse_profile(),
+                profile,
+                "{source:?}"
+            );
+        }
+
+        assert!(!ShaderSource::from("/* #version 450 */\nvoid main() {}").has_version_directive());
+        assert!(ShaderSource::from("#version 450 unknown\n").has_version_directive());
+    }
+
+    #[test]
+    pub fn test_webgl() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 300 es
+precision mediump float;
+
+out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        let limits = ResourceLimits::webgl(WebGlVersion::WebGL2_0);
+        let options = CompilerOptions::webgl(WebGlVersion::WebGL2_0);
+        let input = ShaderInput::new_with_limits(
+            &source,
+            &limits,
+            ShaderStage::Fragment,
+            &options,
+            None,
+            None,
+        )
+        .expect("target");
+        Shader::new(compiler, input).expect("shader init");
+
+        // Versionless sources are GLSL ES 3.00 for WebGL 2.0.
+        let versionless = ShaderSource::from(
+            r#"
+precision mediump float;
+
+out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        let input = ShaderInput::new_with_limits(
+            &versionless,
+            &limits,
+            ShaderStage::Fragment,
+            &options,
+            None,
+            None,
+        )
+        .expect("target");
+        Shader::new(compiler, input).expect("shader init");
+
+        assert!(matches!(
+            ShaderInput::new_with_limits(
+                &source,
+                &limits,
+                ShaderStage::Compute,
+                &options,
+                None,
+                None,
+            ),
+            Err(GlslangError::InvalidTarget(
+                _,
+                TargetError::UnsupportedOpenGlEsStage(
+                    ShaderStage::Compute,
+                    OpenGlEsVersion::OpenGLES3_0
+                )
+            ))
+        ));
+
+        let source = ShaderSource::from("#version 330\nvoid main() {}");
+        assert!(matches!(
+            ShaderInput::new_with_limits(
+                &source,
+                &limits,
+                ShaderStage::Fragment,
+                &options,
+                None,
+                None,
+            ),
+            Err(GlslangError::InvalidProfile(_, 330, GlslProfile::None))
+        ));
+
+        // A #version directive after comments is still verified against the target.
+        let source = ShaderSource::from(
+            "\n// A comment.\n/* Another\ncomment. */\n#version 450\nvoid main() {}",
+        );
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None),
+            Err(GlslangError::InvalidProfile(_, 450, GlslProfile::None))
+        ));
+
+        let source = ShaderSource::from("#version 300 unknown\nvoid main() {}");
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None),
+            Err(GlslangError::InvalidVersionDirective)
+        ));
+
+        // GLSL ES 1.00 only requires for loops with constant bounds.
+        let source = ShaderSource::from(
+            r#"
+precision mediump float;
+
+void main() {
+    float x = 0.0;
+    while (x < 1.0) {
+        x += 0.5;
+    }
+    gl_FragColor = vec4(x);
+}
+        "#,
+        );
+
+        let limits = ResourceLimits::webgl(WebGlVersion::WebGL1_0);
+        let options = CompilerOptions::webgl(WebGlVersion::WebGL1_0);
+        let input = ShaderInput::new_with_limits(
+            &source,
+            &limits,
+            ShaderStage::Fragment,
+            &options,
+            None,
+            None,
+        )
+        .expect("target");
+        assert!(matches!(
+            Shader::new(compiler, input),
+            Err(GlslangError::ParseError(_))
+        ));
+
+        // ShaderInput::new uses the WebGL limits of the options.
+        let input =
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None).expect("target");
+        assert!(matches!(
+            Shader::new(compiler, input),
+            Err(GlslangError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_opengl_versions() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let source = ShaderSource::from(
+            r#"
+#version 410 core
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        let target = Target::OpenGL {
+            version: OpenGlVersion::OpenGL4_1,
+            spirv_version: None,
+        };
+        let limits = ResourceLimits::opengl(OpenGlVersion::OpenGL4_1);
+        let options = CompilerOptions {
+            target,
+            ..CompilerOptions::default()
+        };
+        let input = ShaderInput::new_with_limits(
+            &source,
+            &limits,
+            ShaderStage::Fragment,
+            &options,
+            None,
+            None,
+        )
+        .expect("target");
+        Shader::new(compiler, input).expect("shader init");
+
+        let options = CompilerOptions {
+            target: Target::OpenGL {
+                version: OpenGlVersion::OpenGL3_3,
+                spirv_version: None,
+            },
+            ..CompilerOptions::default()
+        };
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None),
+            Err(GlslangError::InvalidProfile(_, 410, GlslProfile::Core))
+        ));
+
+        let options = CompilerOptions {
+            target: Target::OpenGL {
+                version: OpenGlVersion::OpenGL4_1,
+                spirv_version: Some(SpirvVersion::SPIRV1_0),
+            },
+            ..CompilerOptions::default()
+        };
+        assert!(matches!(
+            ShaderInput::new(&source, ShaderStage::Fragment, &options, None, None),
+            Err(GlslangError::InvalidTarget(
+                _,
+                TargetError::OpenGlSpirvUnsupported(OpenGlVersion::OpenGL4_1)
+            ))
+        ));
+    }
 }
 
 /// The source string of a shader.
@@ -216,49 +1138,101 @@ impl From<&str> for ShaderSource {
 }
 
 impl ShaderSource {
-    pub fn parse_profile(&self) -> Option<(i32, GlslProfile)> {
-        let Ok(string) = self.0.to_str() else {
-            return None;
-        };
+    /// Whether the source has a `#version` directive outside of comments.
+    fn has_version_directive(&self) -> bool {
+        find_version_directive(self.0.as_bytes()).is_some()
+    }
 
-        let Some(string) = string.trim().lines().next() else {
-            return None;
-        };
+    /// Parse the version and profile of the `#version` directive, skipping any comments
+    /// and whitespace before it.
+    ///
+    /// Returns `None` if there is no `#version` directive, or it could not be parsed.
+    pub fn parse_profile(&self) -> Option<(i32, GlslProfile)> {
+        let arguments = find_version_directive(self.0.as_bytes())?;
+        let arguments = std::str::from_utf8(arguments).ok()?;
 
-        let string = string.trim();
-        if !string.starts_with("#version ") {
-            return None;
-        };
+        // Drop any comment following the directive.
+        let arguments = arguments.split("//").next()?.split("/*").next()?;
+        let mut arguments = arguments.split_whitespace();
 
-        let string = string.trim_start_matches("#version ");
-        if string.len() < 3 {
+        let version = arguments.next()?;
+        if !version.bytes().all(|c| c.is_ascii_digit()) {
             return None;
         }
-        let (version, profile) = string.split_at(3);
         let Ok(version) = str::parse::<i32>(version) else {
             return None;
         };
 
-        let profile = profile.trim();
-        let profile = match profile {
-            "compatibility" => GlslProfile::Compatibility,
-            "es" => GlslProfile::ES,
-            "core" => GlslProfile::Core,
-            "" => GlslProfile::None,
-            _ => return None,
+        let profile = match arguments.next() {
+            Some("compatibility") => GlslProfile::Compatibility,
+            Some("es") => GlslProfile::ES,
+            Some("core") => GlslProfile::Core,
+            None => GlslProfile::None,
+            Some(_) => return None,
         };
 
+        if arguments.next().is_some() {
+            return None;
+        }
+
         Some((version, profile))
     }
 }
 
+/// Find the arguments of the first `#version` directive in the source, skipping comments.
+fn find_version_directive(source: &[u8]) -> Option<&[u8]> {
+    let line_end = |s: &[u8]| s.iter().position(|&c| c == b'\n').unwrap_or(s.len());
+
+    let mut rest = source;
+    let mut line_start = true;
+    while let Some(&c) = rest.first() {
+        if rest.starts_with(b"//") {
+            rest = &rest[line_end(rest)..];
+        } else if rest.starts_with(b"/*") {
+            let end = rest[2..]
+                .windows(2)
+                .position(|w| w == b"*/")
+                .map_or(rest.len(), |end| end + 4);
+            rest = &rest[end..];
+        } else if c == b'\n' {
+            line_start = true;
+            rest = &rest[1..];
+        } else if c.is_ascii_whitespace() {
+            rest = &rest[1..];
+        } else if c == b'#' && line_start {
+            let end = line_end(rest);
+            let directive = rest[1..end].trim_ascii_start();
+            if let Some(arguments) = directive.strip_prefix(b"version") {
+                if arguments.first().is_none_or(|c| c.is_ascii_whitespace()) {
+                    return Some(arguments);
+                }
+            }
+
+            // Skip any other directive.
+            line_start = false;
+            rest = &rest[end..];
+        } else {
+            line_start = false;
+            rest = &rest[1..];
+        }
+    }
+
+    None
+}
+
 /// An input to a [`Shader`](crate::Shader).
 #[derive(Clone)]
 pub struct ShaderInput<'a> {
     // Keep these alive.
     _source: &'a ShaderSource,
     _resource: &'a sys::glslang_resource_t,
-    pub(crate) defines: FxHashMap<SmartString<LazyCompact>, Option<SmartString<LazyCompact>>>,
+    pub(crate) defines: Vec<MacroDefine<'static>>,
+    pub(crate) preamble: Option<String>,
+    pub(crate) entry_point: Option<CString>,
+    pub(crate) source_entry_point: Option<CString>,
+    pub(crate) resource_set_binding: Option<HlslResourceSetBinding>,
+    pub(crate) hlsl_options: Option<HlslOptions>,
+    pub(crate) target: Target,
     pub(crate) input: sys::glslang_input_t,
 }
 
@@ -277,23 +1251,125 @@ pub enum VulkanVersion {
     Vulkan1_3,
 }
 
-/// OpenGL Version
+impl VulkanVersion {
+    /// The highest SPIR-V version that is guaranteed to be supported by the Vulkan version.
+    pub const fn max_spirv_version(&self) -> SpirvVersion {
+        match self {
+            VulkanVersion::Vulkan1_0 => SpirvVersion::SPIRV1_0,
+            VulkanVersion::Vulkan1_1 => SpirvVersion::SPIRV1_3,
+            VulkanVersion::Vulkan1_2 => SpirvVersion::SPIRV1_5,
+            VulkanVersion::Vulkan1_3 => SpirvVersion::SPIRV1_6,
+        }
+    }
+}
+
+/// OpenGL Version
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum OpenGlVersion {
+    /// OpenGL 3.3
+    OpenGL3_3,
+    /// OpenGL 4.0
+    OpenGL4_0,
+    /// OpenGL 4.1
+    OpenGL4_1,
+    /// OpenGL 4.2
+    OpenGL4_2,
+    /// OpenGL 4.3
+    OpenGL4_3,
+    /// OpenGL 4.4
+    OpenGL4_4,
+    /// OpenGL 4.5
+    OpenGL4_5,
+    /// OpenGL 4.6
+    OpenGL4_6,
+}
+
+impl OpenGlVersion {
+    /// The highest GLSL version supported by the OpenGL version.
+    pub const fn glsl_version(&self) -> i32 {
+        match self {
+            OpenGlVersion::OpenGL3_3 => 330,
+            OpenGlVersion::OpenGL4_0 => 400,
+            OpenGlVersion::OpenGL4_1 => 410,
+            OpenGlVersion::OpenGL4_2 => 420,
+            OpenGlVersion::OpenGL4_3 => 430,
+            OpenGlVersion::OpenGL4_4 => 440,
+            OpenGlVersion::OpenGL4_5 => 450,
+            OpenGlVersion::OpenGL4_6 => 460,
+        }
+    }
+}
+
+/// OpenGL ES Version
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum OpenGlEsVersion {
+    /// OpenGL ES 2.0, with GLSL ES 1.00
+    OpenGLES2_0,
+    /// OpenGL ES 3.0, with GLSL ES 3.00
+    OpenGLES3_0,
+    /// OpenGL ES 3.1, with GLSL ES 3.10
+    OpenGLES3_1,
+    /// OpenGL ES 3.2, with GLSL ES 3.20
+    OpenGLES3_2,
+}
+
+impl OpenGlEsVersion {
+    /// The highest GLSL ES version supported by the OpenGL ES version.
+    pub const fn glsl_version(&self) -> i32 {
+        match self {
+            OpenGlEsVersion::OpenGLES2_0 => 100,
+            OpenGlEsVersion::OpenGLES3_0 => 300,
+            OpenGlEsVersion::OpenGLES3_1 => 310,
+            OpenGlEsVersion::OpenGLES3_2 => 320,
+        }
+    }
+
+    fn supports_stage(&self, stage: ShaderStage) -> bool {
+        match stage {
+            ShaderStage::Vertex | ShaderStage::Fragment => true,
+            ShaderStage::Compute => self.glsl_version() >= 310,
+            ShaderStage::Geometry
+            | ShaderStage::TesselationControl
+            | ShaderStage::TesselationEvaluation => self.glsl_version() >= 320,
+            _ => false,
+        }
+    }
+}
+
+/// WebGL Version
 #[allow(non_camel_case_types)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[non_exhaustive]
-pub enum OpenGlVersion {
-    /// OpenGL 4.5
-    OpenGL4_5,
+pub enum WebGlVersion {
+    /// WebGL 1.0, based on OpenGL ES 2.0
+    WebGL1_0,
+    /// WebGL 2.0, based on OpenGL ES 3.0
+    WebGL2_0,
+}
+
+impl WebGlVersion {
+    /// The OpenGL ES version the WebGL version is based on.
+    pub const fn opengl_es_version(&self) -> OpenGlEsVersion {
+        match self {
+            WebGlVersion::WebGL1_0 => OpenGlEsVersion::OpenGLES2_0,
+            WebGlVersion::WebGL2_0 => OpenGlEsVersion::OpenGLES3_0,
+        }
+    }
 }
 
 /// The target environment to compile or validate the input shader to.
 ///
 /// If no SPIR-V version is specified, the shader will be unable to be compiled.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Target {
     /// No specified environment.
     ///
-    /// This environment can optionally include a SPIR-V version.
+    /// The shader is validated without any client specific rules, and is not checked against
+    /// an OpenGL version. This environment can optionally include a SPIR-V version.
     None(Option<SpirvVersion>),
     /// Validate the shader against Vulkan semantics. Vulkan requires GLSL 140 or above.
     Vulkan {
@@ -306,17 +1382,130 @@ pub enum Target {
     },
     /// Validate the shader against OpenGL semantics.
     OpenGL {
-        /// The OpenGL version to validate against. Currently only OpenGL 4.5 is supported.
+        /// The OpenGL version to validate against.
         version: OpenGlVersion,
-        /// An optional SPIR-V version if targeting OpenGL SPIR-V. Requires GLSL 330 or above.
+        /// An optional SPIR-V version if targeting OpenGL SPIR-V. Requires GLSL 330 or above,
+        /// and OpenGL 4.5 or above.
         spirv_version: Option<SpirvVersion>,
     },
+    /// Validate the shader against OpenGL ES semantics.
+    ///
+    /// Only shaders with the ES profile are accepted, and they can not be compiled to SPIR-V.
+    OpenGlEs {
+        /// The OpenGL ES version to validate against.
+        version: OpenGlEsVersion,
+    },
 }
 
 impl Target {
+    /// Target the Vulkan version with the highest SPIR-V version it supports.
+    pub const fn vulkan(version: VulkanVersion) -> Self {
+        Target::Vulkan {
+            version,
+            spirv_version: version.max_spirv_version(),
+        }
+    }
+
+    /// Validate against the OpenGL ES version the WebGL version is based on.
+    ///
+    /// This should be used with the matching [`ResourceLimits::webgl`](crate::limits::ResourceLimits::webgl)
+    /// limits.
+    pub const fn webgl(version: WebGlVersion) -> Self {
+        Target::OpenGlEs {
+            version: version.opengl_es_version(),
+        }
+    }
+
+    /// Verify that the target can be used with the source language, shader stage, and
+    /// GLSL version profile.
+    fn verify(
+        &self,
+        language: SourceLanguage,
+        stage: ShaderStage,
+        profile: Option<&(i32, GlslProfile)>,
+    ) -> Result<(), GlslangError> {
+        let invalid = |reason| Err(GlslangError::InvalidTarget(self.clone(), reason));
+
+        // SPIR-V versions are ordered by their numeric value.
+        let max_spirv_version = match self {
+            Target::None(_) => SpirvVersion::SPIRV1_6,
+            Target::Vulkan { version, .. } => version.max_spirv_version(),
+            // GL_ARB_gl_spirv only supports SPIR-V 1.0
+            Target::OpenGL { .. } | Target::OpenGlEs { .. } => SpirvVersion::SPIRV1_0,
+        };
+
+        let spirv_version = match self {
+            Target::None(spirv_version) | Target::OpenGL { spirv_version, .. } => *spirv_version,
+            Target::Vulkan { spirv_version, .. } => Some(*spirv_version),
+            Target::OpenGlEs { .. } => None,
+        };
+
+        if let Some(spirv_version) = spirv_version {
+            if spirv_version as i32 > max_spirv_version as i32 {
+                return invalid(TargetError::UnsupportedSpirvVersion {
+                    spirv_version,
+                    max_spirv_version,
+                });
+            }
+        }
+
+        if let Target::OpenGL {
+            version,
+            spirv_version: Some(_),
+        } = self
+        {
+            if version.glsl_version() < 450 {
+                return invalid(TargetError::OpenGlSpirvUnsupported(*version));
+            }
+        }
+
+        if language == SourceLanguage::HLSL
+            && matches!(self, Target::OpenGL { .. } | Target::OpenGlEs { .. })
+        {
+            return invalid(TargetError::HlslForOpenGl);
+        }
+
+        if let Target::OpenGlEs { version } = self {
+            if !version.supports_stage(stage) {
+                return invalid(TargetError::UnsupportedOpenGlEsStage(stage, *version));
+            }
+        }
+
+        if matches!(
+            stage,
+            ShaderStage::Mesh
+                | ShaderStage::Task
+                | ShaderStage::RayGeneration
+                | ShaderStage::Intersect
+                | ShaderStage::AnyHit
+                | ShaderStage::ClosestHit
+                | ShaderStage::Miss
+                | ShaderStage::Callable
+        ) && !matches!(self, Target::Vulkan { spirv_version, .. }
+            if *spirv_version as i32 >= SpirvVersion::SPIRV1_4 as i32)
+        {
+            return invalid(TargetError::UnsupportedStage(stage));
+        }
+
+        if language == SourceLanguage::GLSL {
+            self.verify_glsl_profile(profile)?;
+        }
+
+        Ok(())
+    }
+
+    /// The limits used if none are given explicitly.
+    fn default_limits(&self) -> &'static ResourceLimits {
+        match self {
+            Target::OpenGlEs { version } => limits::opengl_es_limits(*version),
+            _ => &limits::DEFAULT_LIMITS,
+        }
+    }
+
     fn env(&self) -> sys::glslang_client_t {
         match self {
-            Target::None(_) => sys::glslang_client_t::None,
+            // glslang has no OpenGL ES client, the ES profile is validated without one.
+            Target::None(_) | Target::OpenGlEs { .. } => sys::glslang_client_t::None,
             Target::Vulkan { .. } => sys::glslang_client_t::Vulkan,
             Target::OpenGL { .. } => sys::glslang_client_t::OpenGL,
         }
@@ -332,22 +1521,24 @@ impl Target {
                 }
             }
             Target::Vulkan { .. } => sys::glslang_target_language_t::SPIRV,
+            Target::OpenGlEs { .. } => sys::glslang_target_language_t::None,
         }
     }
 
     fn env_version(&self) -> sys::glslang_target_client_version_t {
         match self {
             // Doesn't matter.
-            Target::None(_) => sys::glslang_target_client_version_t::OpenGL450,
+            Target::None(_) | Target::OpenGlEs { .. } => {
+                sys::glslang_target_client_version_t::OpenGL450
+            }
             Target::Vulkan { version, .. } => match version {
                 VulkanVersion::Vulkan1_0 => sys::glslang_target_client_version_t::Vulkan1_0,
                 VulkanVersion::Vulkan1_1 => sys::glslang_target_client_version_t::Vulkan1_1,
                 VulkanVersion::Vulkan1_2 => sys::glslang_target_client_version_t::Vulkan1_2,
                 VulkanVersion::Vulkan1_3 => sys::glslang_target_client_version_t::Vulkan1_3,
             },
-            Target::OpenGL { version, .. } => match version {
-                OpenGlVersion::OpenGL4_5 => sys::glslang_target_client_version_t::OpenGL450,
-            },
+            // glslang only supports OpenGL 4.5 as the client for OpenGL SPIR-V.
+            Target::OpenGL { .. } => sys::glslang_target_client_version_t::OpenGL450,
         }
     }
 
@@ -358,6 +1549,7 @@ impl Target {
                 spirv_version.unwrap_or(sys::glslang_target_language_version_t::SPIRV1_0)
             }
             Target::Vulkan { spirv_version, .. } => *spirv_version,
+            Target::OpenGlEs { .. } => sys::glslang_target_language_version_t::SPIRV1_0,
         }
     }
 
@@ -396,6 +1588,14 @@ impl Target {
             return Err(GlslangError::VersionUnsupported(version, profile));
         }
 
+        // ES shaders for SPIR-V require version 310
+        if profile == GlslProfile::ES
+            && version < 310
+            && self.target_spirv() == sys::glslang_target_language_t::SPIRV
+        {
+            return Err(GlslangError::InvalidProfile(self.clone(), version, profile));
+        }
+
         match self {
             Target::None(spirv_version) => {
                 if spirv_version.is_some() && profile == GlslProfile::Compatibility {
@@ -421,7 +1621,14 @@ impl Target {
                     ));
                 }
             }
-            Target::OpenGL { spirv_version, .. } => {
+            Target::OpenGL {
+                version: gl_version,
+                spirv_version,
+            } => {
+                if version > gl_version.glsl_version() {
+                    return Err(GlslangError::InvalidProfile(self.clone(), version, profile));
+                }
+
                 if spirv_version.is_some() {
                     // OpenGL SPIRV needs 330+
                     if version < 330 {
@@ -438,6 +1645,15 @@ impl Target {
                     }
                 }
             }
+            Target::OpenGlEs {
+                version: es_version,
+            } => {
+                // GLSL ES 1.00 has no profile token, desktop profiles are not accepted.
+                let is_es = profile == GlslProfile::ES || version == 100;
+                if !is_es || version > es_version.glsl_version() {
+                    return Err(GlslangError::InvalidProfile(self.clone(), version, profile));
+                }
+            }
         }
 
         Ok(())
@@ -470,6 +1686,15 @@ bitflags! {
     }
 }
 
+impl ShaderMessage {
+    /// Messages that are only valid when compiling HLSL sources.
+    const HLSL_ONLY: ShaderMessage = ShaderMessage::READ_HLSL
+        .union(ShaderMessage::HLSL_OFFSETS)
+        .union(ShaderMessage::HLSL_ENABLE_16BIT_TYPES)
+        .union(ShaderMessage::HLSL_LEGALIZATION)
+        .union(ShaderMessage::HLSL_DX9_COMPATIBLE);
+}
+
 impl From<ShaderMessage> for sys::glslang_messages_t {
     fn from(value: ShaderMessage) -> Self {
         sys::glslang_messages_t(value.bits())
@@ -484,10 +1709,100 @@ pub struct CompilerOptions {
     /// The target
     pub target: Target,
     /// The GLSL version profile.
-    /// If specified, will force the specified profile on compilation.
+    /// If specified, will force the specified profile on compilation, overriding any `#version`
+    /// directive in the source.
     pub version_profile: Option<(i32, GlslProfile)>,
+    /// The GLSL version profile to use if the source has no `#version` directive.
+    ///
+    /// If not specified, glslang's default of version 100 is used, which is GLSL ES 1.00.
+    /// Versionless desktop shaders should specify `(110, GlslProfile::None)` here, and
+    /// versionless GLSL ES 3.00 shaders `(300, GlslProfile::ES)`.
+    /// This is ignored if [`version_profile`](Self::version_profile) is specified.
+    pub default_version_profile: Option<(i32, GlslProfile)>,
+    /// Compile in forward-compatible mode, where features deprecated by the GLSL version
+    /// are unavailable.
+    pub forward_compatible: bool,
     /// Messages for glslang to emit
     pub messages: ShaderMessage,
+    /// Options for compiling HLSL sources.
+    ///
+    /// Only valid if the source language is [`SourceLanguage::HLSL`](crate::SourceLanguage::HLSL).
+    pub hlsl: Option<HlslOptions>,
+}
+
+impl CompilerOptions {
+    /// Compiler options for validating GLSL ES shaders for WebGL.
+    ///
+    /// Sources without a `#version` directive are validated against the GLSL ES version
+    /// of the WebGL version. [`ShaderInput::new`] uses the matching
+    /// [`ResourceLimits::webgl`](crate::limits::ResourceLimits::webgl) limits.
+    pub fn webgl(version: WebGlVersion) -> Self {
+        let default_version_profile = match version {
+            WebGlVersion::WebGL1_0 => (100, GlslProfile::None),
+            WebGlVersion::WebGL2_0 => (300, GlslProfile::ES),
+        };
+
+        Self {
+            source_language: SourceLanguage::GLSL,
+            target: Target::webgl(version),
+            default_version_profile: Some(default_version_profile),
+            ..Self::default()
+        }
+    }
+
+    /// The messages to pass to glslang, including any implied by the HLSL options.
+    fn effective_messages(&self) -> ShaderMessage {
+        let mut messages = self.messages;
+        if self.source_language == SourceLanguage::HLSL {
+            messages |= ShaderMessage::READ_HLSL;
+        }
+        if let Some(hlsl) = &self.hlsl {
+            messages |= hlsl.messages();
+        }
+        messages
+    }
+
+    fn verify_hlsl_options(&self) -> Result<(), GlslangError> {
+        if self.source_language == SourceLanguage::HLSL {
+            return Ok(());
+        }
+
+        if self.hlsl.is_some() || self.messages.intersects(ShaderMessage::HLSL_ONLY) {
+            return Err(GlslangError::UnexpectedHlslOptions(self.source_language));
+        }
+
+        Ok(())
+    }
+}
+
+/// Options specific to compiling HLSL sources.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct HlslOptions {
+    /// Enable 16-bit types such as `half` and `min16float` as true 16-bit types.
+    pub enable_16bit_types: bool,
+    /// Enable DirectX 9 compatibility, i.e. `POSITION` and `COLOR` semantics on fragment shaders.
+    pub dx9_compatible: bool,
+    /// Use HLSL offset rules for packing members of uniform and storage blocks.
+    pub offsets: bool,
+    /// Map HLSL registers to bindings when mapping IO.
+    pub io_mapping: bool,
+    /// Flatten arrays of uniforms into individual uniforms.
+    pub flatten_uniform_arrays: bool,
+    /// Invert the Y coordinate of the vertex position output.
+    pub invert_y: bool,
+}
+
+impl HlslOptions {
+    fn messages(&self) -> ShaderMessage {
+        let mut messages = ShaderMessage::empty();
+        messages.set(
+            ShaderMessage::HLSL_ENABLE_16BIT_TYPES,
+            self.enable_16bit_types,
+        );
+        messages.set(ShaderMessage::HLSL_DX9_COMPATIBLE, self.dx9_compatible);
+        messages.set(ShaderMessage::HLSL_OFFSETS, self.offsets);
+        messages
+    }
 }
 
 impl Default for CompilerOptions {
@@ -499,18 +1814,26 @@ impl Default for CompilerOptions {
                 spirv_version: SpirvVersion::SPIRV1_0,
             },
             version_profile: None,
+            default_version_profile: None,
+            forward_compatible: false,
             messages: ShaderMessage::DEFAULT,
+            hlsl: None,
         }
     }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 /// A `#define` macro to expand in the source code.
+///
+/// Function-like macros are specified by including the parameter list in the name,
+/// i.e. `NAME(a,b)`.
 pub struct MacroDefine<'a> {
-    /// The name of the macro.
+    /// The name of the macro, including the parameter list for function-like macros.
     name: Cow<'a, str>,
     /// The replacement value of the macro, if any.
     value: Option<Cow<'a, str>>,
+    /// Whether the macro should be undefined instead.
+    undef: bool,
 }
 
 impl MacroDefine<'_> {
@@ -519,6 +1842,7 @@ impl MacroDefine<'_> {
         MacroDefine {
             name: Cow::Borrowed(name),
             value: value.map(Cow::Borrowed),
+            undef: false,
         }
     }
 
@@ -527,24 +1851,237 @@ impl MacroDefine<'_> {
         MacroDefine {
             name: Cow::Owned(name),
             value: value.map(Cow::Owned),
+            undef: false,
+        }
+    }
+
+    /// Create a new `#undef` from a string slice.
+    pub fn undef_from_str(name: &str) -> MacroDefine<'_> {
+        MacroDefine {
+            name: Cow::Borrowed(name),
+            value: None,
+            undef: true,
+        }
+    }
+
+    /// Create a new `#undef` from an owned string.
+    pub fn undef_from_string(name: String) -> MacroDefine<'static> {
+        MacroDefine {
+            name: Cow::Owned(name),
+            value: None,
+            undef: true,
+        }
+    }
+
+    /// The name of the macro, without the parameter list of function-like macros.
+    pub fn name(&self) -> &str {
+        self.name
+            .split_once('(')
+            .map_or(&self.name, |(identifier, _)| identifier)
+    }
+
+    /// The replacement value of the macro, if any.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    /// Whether this is an `#undef` rather than a `#define`.
+    pub fn is_undef(&self) -> bool {
+        self.undef
+    }
+
+    fn is_identifier(s: &str) -> bool {
+        let mut chars = s.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    /// Verify that the macro can be safely pasted into the preamble.
+    fn validate(&self) -> Result<(), GlslangError> {
+        let invalid = || GlslangError::InvalidMacroDefine(self.name.to_string());
+
+        if !Self::is_identifier(self.name()) {
+            return Err(invalid());
+        }
+
+        if let Some(parameters) = self.name.strip_prefix(self.name()) {
+            if !parameters.is_empty() {
+                // Function-like macros can not be undefined with their parameter list.
+                if self.undef {
+                    return Err(invalid());
+                }
+
+                let Some(parameters) = parameters
+                    .strip_prefix('(')
+                    .and_then(|p| p.strip_suffix(')'))
+                else {
+                    return Err(invalid());
+                };
+
+                if !parameters.trim().is_empty() {
+                    let parameters = parameters.split(',').map(str::trim).collect::<Vec<_>>();
+                    if !parameters.iter().all(|p| Self::is_identifier(p)) {
+                        return Err(invalid());
+                    }
+
+                    if (1..parameters.len()).any(|i| parameters[..i].contains(&parameters[i])) {
+                        return Err(invalid());
+                    }
+                }
+            }
+        }
+
+        if let Some(value) = &self.value {
+            // The value must not end the directive early, nor continue it into or comment out
+            // the rest of the preamble. A NUL could not be passed to glslang at all.
+            if self.undef
+                || value.contains(['\n', '\r', '\0'])
+                || value.ends_with('\\')
+                || Self::has_unterminated_comment(value)
+            {
+                return Err(invalid());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a block comment in the string is not closed before its end.
+    fn has_unterminated_comment(mut s: &str) -> bool {
+        loop {
+            let block = s.find("/*");
+            match (block, s.find("//")) {
+                // A line comment ends with the directive.
+                (Some(block), Some(line)) if line < block => return false,
+                (Some(block), _) => match s[block + 2..].find("*/") {
+                    Some(end) => s = &s[block + 2 + end + 2..],
+                    None => return true,
+                },
+                (None, _) => return false,
+            }
+        }
+    }
+
+    /// The preprocessor directive for the preamble.
+    fn directive(&self) -> String {
+        match (&self.value, self.undef) {
+            (_, true) => format!("#undef {}\n", self.name),
+            (Some(value), false) => format!("#define {} {}\n", self.name, value),
+            (None, false) => format!("#define {}\n", self.name),
+        }
+    }
+
+    fn into_owned(self) -> MacroDefine<'static> {
+        MacroDefine {
+            name: Cow::Owned(self.name.into_owned()),
+            value: self.value.map(|value| Cow::Owned(value.into_owned())),
+            undef: self.undef,
+        }
+    }
+}
+
+impl MacroDefine<'static> {
+    /// Parse a list of define strings, with the same semantics as `-D` in glslangValidator.
+    ///
+    /// The parsed defines can be added to a [`ShaderInput`](crate::ShaderInput) with
+    /// [`ShaderInput::add_define`](crate::ShaderInput::add_define).
+    pub fn parse_all<S: AsRef<str>>(
+        defines: impl IntoIterator<Item = S>,
+    ) -> Result<Vec<MacroDefine<'static>>, GlslangError> {
+        defines
+            .into_iter()
+            .map(|define| define.as_ref().parse())
+            .collect()
+    }
+}
+
+/// Parses a define string with the same semantics as `-D` in glslangValidator.
+///
+/// `NAME` and `NAME=` define an empty macro, and `NAME=VALUE` defines `NAME` as `VALUE`.
+/// Only the first `=` separates the name from the value.
+impl FromStr for MacroDefine<'static> {
+    type Err = GlslangError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let define = match s.split_once('=') {
+            Some((name, value)) => {
+                MacroDefine::new_from_string(name.to_string(), Some(value.to_string()))
+            }
+            None => MacroDefine::new_from_string(s.to_string(), None),
+        };
+
+        define.validate()?;
+        Ok(define)
+    }
+}
+
+/// Formats the define as it would be passed to `-D` in glslangValidator.
+///
+/// For an `#undef`, only the name is written, as it would be passed to `-U`.
+impl Display for MacroDefine<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.value, self.undef) {
+            (Some(value), false) => write!(f, "{}={}", self.name, value),
+            _ => write!(f, "{}", self.name),
         }
     }
 }
 
 impl<'a> From<(&'a str, Option<&'a str>)> for MacroDefine<'a> {
     fn from(value: (&'a str, Option<&'a str>)) -> Self {
-        MacroDefine {
-            name: Cow::Borrowed(value.0),
-            value: value.1.map(Cow::Borrowed),
-        }
+        MacroDefine::new_from_str(value.0, value.1)
     }
 }
 
 impl<'a> From<&'a (&'a str, Option<&'a str>)> for MacroDefine<'a> {
     fn from(value: &'a (&'a str, Option<&'a str>)) -> Self {
-        MacroDefine {
-            name: Cow::Borrowed(value.0),
-            value: value.1.map(Cow::Borrowed),
+        MacroDefine::new_from_str(value.0, value.1)
+    }
+}
+
+/// An override of the descriptor set and binding of a single HLSL register.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct HlslRegisterBinding {
+    /// The register to remap, i.e. `t0`, `s1`, `b2`, or `u3`.
+    pub register: String,
+    /// The descriptor set to place the resource in.
+    pub set: u32,
+    /// The binding of the resource within the descriptor set.
+    pub binding: u32,
+}
+
+impl HlslRegisterBinding {
+    /// Whether the register is a register type followed by its number.
+    fn is_valid_register(&self) -> bool {
+        self.register
+            .strip_prefix(|c: char| c.is_ascii_alphabetic())
+            .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+    }
+}
+
+/// Mapping of HLSL register spaces and resources to Vulkan descriptor sets.
+///
+/// Resources with an explicit `[[vk::binding]]` are not affected. glslang does not support
+/// combining a global descriptor set with per-register overrides.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum HlslResourceSetBinding {
+    /// Place every resource in the given descriptor set.
+    Global(u32),
+    /// Place the given registers in the specified descriptor set and binding.
+    PerRegister(Vec<HlslRegisterBinding>),
+}
+
+impl HlslResourceSetBinding {
+    /// The string list expected by `glslang_shader_set_resource_set_binding`.
+    fn to_strings(&self) -> Vec<String> {
+        match self {
+            HlslResourceSetBinding::Global(set) => vec![set.to_string()],
+            HlslResourceSetBinding::PerRegister(registers) => registers
+                .iter()
+                .flat_map(|r| [r.register.clone(), r.set.to_string(), r.binding.to_string()])
+                .collect(),
         }
     }
 }
@@ -552,6 +2089,9 @@ impl<'a> From<&'a (&'a str, Option<&'a str>)> for MacroDefine<'a> {
 /// The input to a shader instance.
 impl<'a> ShaderInput<'a> {
     /// Create a new [`ShaderInput`](crate::ShaderInput) with default limits.
+    ///
+    /// For an OpenGL ES target, including [`CompilerOptions::webgl`], these are the minimum
+    /// limits of the OpenGL ES version. Otherwise glslang's default limits are used.
     pub fn new<'def, D: 'def>(
         source: &'a ShaderSource,
         stage: ShaderStage,
@@ -564,7 +2104,7 @@ impl<'a> ShaderInput<'a> {
     {
         Self::new_with_limits(
             source,
-            &limits::DEFAULT_LIMITS,
+            options.target.default_limits(),
             stage,
             options,
             defines,
@@ -572,6 +2112,31 @@ impl<'a> ShaderInput<'a> {
         )
     }
 
+    /// Create a new [`ShaderInput`](crate::ShaderInput) for an HLSL source with default limits.
+    ///
+    /// The shader stage and compiler options are derived from the given [`HlslProfile`](crate::HlslProfile).
+    pub fn new_with_hlsl_profile<'def, D: 'def>(
+        source: &'a ShaderSource,
+        profile: &HlslProfile,
+        defines: Option<&'def [D]>,
+        include_handler: Option<&'a mut dyn IncludeHandler>,
+    ) -> Result<Self, GlslangError>
+    where
+        MacroDefine<'def>: From<&'def D>,
+    {
+        let Some(stage) = profile.stage else {
+            return Err(GlslangError::UnsupportedHlslProfile(*profile));
+        };
+
+        Self::new(
+            source,
+            stage,
+            &profile.compiler_options(),
+            defines,
+            include_handler,
+        )
+    }
+
     /// Create a new [`ShaderInput`](crate::ShaderInput) with the specified resource limits.
     pub fn new_with_limits<'def, D: 'def>(
         source: &'a ShaderSource,
@@ -584,33 +2149,46 @@ impl<'a> ShaderInput<'a> {
     where
         MacroDefine<'def>: From<&'def D>,
     {
+        let parsed_profile = source.parse_profile();
+
+        // Verifying the default profile instead would let any #version directive through.
+        if parsed_profile.is_none() && source.has_version_directive() {
+            return Err(GlslangError::InvalidVersionDirective);
+        }
+
         let profile = options
             .version_profile
-            .map_or_else(|| source.parse_profile(), |p| Some(p));
+            .or(parsed_profile)
+            .or(options.default_version_profile);
 
-        if options.source_language == SourceLanguage::GLSL {
-            options.target.verify_glsl_profile(profile.as_ref())?;
-        }
+        options
+            .target
+            .verify(options.source_language, stage, profile.as_ref())?;
+
+        options.verify_hlsl_options()?;
+
+        // glslang only has a single default version, which is forced if requested.
+        let default_version_profile = options.version_profile.or(options.default_version_profile);
+
+        // glslang ignores the default profile unless it is forced. Without a #version directive
+        // there is nothing to override, so forcing the default is the same as defaulting to it.
+        let force_default_version_and_profile = options.version_profile.is_some()
+            || (options.default_version_profile.is_some() && !source.has_version_directive());
 
         let callbacks_ctx = include_handler.map_or(core::ptr::null_mut(), |callback| {
             Box::into_raw(Box::new(callback))
         });
 
-        Ok(Self {
+        let mut input = Self {
             _source: source,
             _resource: &resource.0,
-            defines: defines.map_or(FxHashMap::default(), |defines| {
-                defines
-                    .into_iter()
-                    .map(|v| {
-                        let v: MacroDefine = MacroDefine::from(v);
-                        (
-                            SmartString::from(v.name),
-                            v.value.map(|s| SmartString::from(s)),
-                        )
-                    })
-                    .collect()
-            }),
+            defines: Vec::new(),
+            preamble: None,
+            entry_point: None,
+            source_entry_point: None,
+            resource_set_binding: None,
+            hlsl_options: options.hlsl.clone(),
+            target: options.target.clone(),
             input: sys::glslang_input_t {
                 language: options.source_language,
                 stage,
@@ -619,11 +2197,11 @@ impl<'a> ShaderInput<'a> {
                 target_language: options.target.target_spirv(),
                 target_language_version: options.target.spirv_version(),
                 code: source.0.as_ptr(),
-                default_version: options.version_profile.map_or(100, |o| o.0),
-                default_profile: options.version_profile.map_or(GlslProfile::None, |o| o.1),
-                force_default_version_and_profile: options.version_profile.map_or(0, |_| 1),
-                forward_compatible: 0,
-                messages: options.messages.into(),
+                default_version: default_version_profile.map_or(100, |o| o.0),
+                default_profile: default_version_profile.map_or(GlslProfile::None, |o| o.1),
+                force_default_version_and_profile: force_default_version_and_profile.into(),
+                forward_compatible: options.forward_compatible.into(),
+                messages: options.effective_messages().into(),
                 resource: &resource.0,
                 callbacks: glsl_include_callbacks_s {
                     include_system: Some(include::_glslang_rs_sys_func),
@@ -632,6 +2210,127 @@ impl<'a> ShaderInput<'a> {
                 },
                 callbacks_ctx: callbacks_ctx as *mut c_void,
             },
-        })
+        };
+
+        for define in defines.unwrap_or_default() {
+            input.add_define(MacroDefine::from(define))?;
+        }
+
+        Ok(input)
+    }
+
+    /// Create a new [`ShaderInput`](crate::ShaderInput) from already preprocessed source with default limits.
+    ///
+    /// The source is expected to be the output of [`Compiler::preprocess`](crate::Compiler::preprocess).
+    /// Since includes and defines have already been expanded, no include handler or defines are used.
+    pub fn from_preprocessed(
+        source: &'a ShaderSource,
+        stage: ShaderStage,
+        options: &CompilerOptions,
+    ) -> Result<Self, GlslangError> {
+        Self::from_preprocessed_with_limits(source, &limits::DEFAULT_LIMITS, stage, options)
+    }
+
+    /// Create a new [`ShaderInput`](crate::ShaderInput) from already preprocessed source with the
+    /// specified resource limits.
+    pub fn from_preprocessed_with_limits(
+        source: &'a ShaderSource,
+        resource: &'a ResourceLimits,
+        stage: ShaderStage,
+        options: &CompilerOptions,
+    ) -> Result<Self, GlslangError> {
+        Self::new_with_limits::<(&str, Option<&str>)>(source, resource, stage, options, None, None)
+    }
+
+    /// Set the name of the entry point in the generated SPIR-V.
+    ///
+    /// If no source entry point is set, this is also the name of the function in the source
+    /// that is used as the entry point. This is required to compile HLSL shaders whose entry
+    /// point is not named `main`.
+    ///
+    /// # Panics
+    /// Panics if `name` contains a null byte.
+    pub fn set_entry_point(&mut self, name: &str) {
+        self.entry_point = Some(CString::new(name).expect("Invalid entry point name"));
+    }
+
+    /// Set the name of the function in the source that is used as the entry point.
+    ///
+    /// This allows selecting one of multiple entry points in a single source file, while
+    /// the name in the generated SPIR-V is controlled by [`set_entry_point`](Self::set_entry_point).
+    ///
+    /// # Panics
+    /// Panics if `name` contains a null byte.
+    pub fn set_source_entry_point(&mut self, name: &str) {
+        self.source_entry_point =
+            Some(CString::new(name).expect("Invalid source entry point name"));
+    }
+
+    /// Set the mapping of HLSL registers to descriptor sets.
+    ///
+    /// This is applied before parsing and only affects HLSL sources.
+    /// Returns [`GlslangError::InvalidHlslRegister`] if a register is not a register name like `t0`.
+    pub fn set_resource_set_binding(
+        &mut self,
+        binding: HlslResourceSetBinding,
+    ) -> Result<(), GlslangError> {
+        if let HlslResourceSetBinding::PerRegister(registers) = &binding {
+            if let Some(invalid) = registers.iter().find(|r| !r.is_valid_register()) {
+                return Err(GlslangError::InvalidHlslRegister(invalid.register.clone()));
+            }
+        }
+
+        self.resource_set_binding = Some(binding);
+        Ok(())
+    }
+
+    /// Set text that is treated as if it appeared immediately after the `#version` directive,
+    /// following any defines.
+    ///
+    /// The preamble is not part of the source, so line numbers in diagnostics are unaffected.
+    /// This can be used to add `#extension` directives or helper functions without modifying
+    /// the shader.
+    ///
+    /// # Panics
+    /// Panics if `preamble` contains a null byte.
+    pub fn set_preamble(&mut self, preamble: &str) {
+        assert!(!preamble.contains('\0'), "Invalid preamble format");
+        self.preamble = Some(preamble.to_string());
+    }
+
+    /// The messages to link a program containing this shader with.
+    pub(crate) fn link_messages(&self) -> ShaderMessage {
+        let mut messages = ShaderMessage::from_bits_retain(self.input.messages.0);
+        if self.input.client == sys::glslang_client_t::Vulkan {
+            messages |= ShaderMessage::VULKAN_RULES;
+        }
+        if self.input.target_language == sys::glslang_target_language_t::SPIRV {
+            messages |= ShaderMessage::SPV_RULES;
+        }
+        messages
+    }
+
+    /// Add a `#define` or `#undef` to the preamble, after any previously added defines.
+    ///
+    /// Returns an error if the define is not a valid preprocessor identifier or parameter list,
+    /// if the value spans multiple lines, or if the macro is already defined.
+    pub fn add_define(&mut self, define: MacroDefine) -> Result<(), GlslangError> {
+        define.validate()?;
+
+        let defined = self
+            .defines
+            .iter()
+            .rev()
+            .find(|d| d.name() == define.name())
+            .is_some_and(|d| !d.is_undef());
+
+        if defined && !define.is_undef() {
+            return Err(GlslangError::DuplicateMacroDefine(
+                define.name().to_string(),
+            ));
+        }
+
+        self.defines.push(define.into_owned());
+        Ok(())
     }
 }
@@ -0,0 +1,586 @@
+use crate::ctypes::{ShaderStage, SpirvVersion};
+use crate::error::GlslangError;
+use bitflags::bitflags;
+use glslang_sys as sys;
+use std::ffi::CStr;
+use std::io::Write;
+use std::ops::Deref;
+use std::path::Path;
+
+/// The magic number at the start of every SPIR-V module.
+pub const MAGIC_NUMBER: u32 = 0x0723_0203;
+
+/// The number of words in the header of a SPIR-V module.
+pub const HEADER_LENGTH: usize = 5;
+
+/// The largest id bound allowed by the universal limits of SPIR-V.
+const MAX_ID_BOUND: u32 = 0x3f_ffff;
+
+const OP_ENTRY_POINT: u32 = 15;
+const OP_CAPABILITY: u32 = 17;
+
+/// A SPIR-V module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpirvModule {
+    words: Vec<u32>,
+}
+
+/// SPIR-V words borrowed from a [`LinkedProgram`](crate::LinkedProgram).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpirvView<'a> {
+    words: &'a [u32],
+}
+
+impl<'a> SpirvView<'a> {
+    pub(crate) fn new(words: &'a [u32]) -> Self {
+        Self { words }
+    }
+
+    /// The borrowed words.
+    pub fn words(&self) -> &'a [u32] {
+        self.words
+    }
+
+    /// Copy the words into a new vector.
+    pub fn into_vec(self) -> Vec<u32> {
+        self.words.to_vec()
+    }
+
+    /// Copy the words into a new [`SpirvModule`].
+    pub fn to_module(&self) -> SpirvModule {
+        SpirvModule::from_words_unchecked(self.words.to_vec())
+    }
+}
+
+impl Deref for SpirvView<'_> {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        self.words
+    }
+}
+
+impl AsRef<[u32]> for SpirvView<'_> {
+    fn as_ref(&self) -> &[u32] {
+        self.words
+    }
+}
+
+/// An `OpEntryPoint` of a [`SpirvModule`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EntryPoint {
+    /// The `ExecutionModel` of the entry point.
+    pub execution_model: u32,
+    /// The id of the entry point function.
+    pub id: u32,
+    /// The name of the entry point.
+    pub name: String,
+}
+
+impl EntryPoint {
+    /// The shader stage of the execution model, if it is a graphics or compute stage.
+    pub fn stage(&self) -> Option<ShaderStage> {
+        Some(match self.execution_model {
+            0 => ShaderStage::Vertex,
+            1 => ShaderStage::TesselationControl,
+            2 => ShaderStage::TesselationEvaluation,
+            3 => ShaderStage::Geometry,
+            4 => ShaderStage::Fragment,
+            5 => ShaderStage::Compute,
+            5267 | 5364 => ShaderStage::Task,
+            5268 | 5365 => ShaderStage::Mesh,
+            5313 => ShaderStage::RayGeneration,
+            5314 => ShaderStage::Intersect,
+            5315 => ShaderStage::AnyHit,
+            5316 => ShaderStage::ClosestHit,
+            5317 => ShaderStage::Miss,
+            5318 => ShaderStage::Callable,
+            _ => return None,
+        })
+    }
+}
+
+impl SpirvModule {
+    /// Create a module from SPIR-V words in native endianness.
+    ///
+    /// Returns [`GlslangError::InvalidSpirv`] if the words are not a well-formed module.
+    pub fn from_words(words: Vec<u32>) -> Result<Self, GlslangError> {
+        check_module(&words)?;
+        Ok(Self { words })
+    }
+
+    /// Create a module from bytes in either endianness, as determined by the magic number.
+    ///
+    /// Returns [`GlslangError::InvalidSpirv`] if the bytes are not a well-formed module.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, GlslangError> {
+        let chunks = bytes.chunks_exact(4);
+        if !chunks.remainder().is_empty() {
+            return Err(GlslangError::InvalidSpirv(format!(
+                "module is {} bytes long, which is not a multiple of 4",
+                bytes.len()
+            )));
+        }
+
+        let words = chunks.map(|chunk| chunk.try_into().unwrap());
+        let words: Vec<u32> = match bytes.get(..4) {
+            Some(magic) if magic == MAGIC_NUMBER.to_le_bytes() => {
+                words.map(u32::from_le_bytes).collect()
+            }
+            Some(magic) if magic == MAGIC_NUMBER.to_be_bytes() => {
+                words.map(u32::from_be_bytes).collect()
+            }
+            _ => words.map(u32::from_le_bytes).collect(),
+        };
+
+        Self::from_words(words)
+    }
+
+    /// Wrap the output of the compiler, which is always well-formed.
+    pub(crate) fn from_words_unchecked(words: Vec<u32>) -> Self {
+        Self { words }
+    }
+
+    /// The words of the module.
+    pub fn words(&self) -> &[u32] {
+        &self.words
+    }
+
+    /// Take the words of the module.
+    pub fn into_vec(self) -> Vec<u32> {
+        self.words
+    }
+
+    /// The SPIR-V version of the module, or `None` if the version is not known to glslang.
+    pub fn version(&self) -> Option<SpirvVersion> {
+        Some(match self.words[1] {
+            0x0001_0000 => SpirvVersion::SPIRV1_0,
+            0x0001_0100 => SpirvVersion::SPIRV1_1,
+            0x0001_0200 => SpirvVersion::SPIRV1_2,
+            0x0001_0300 => SpirvVersion::SPIRV1_3,
+            0x0001_0400 => SpirvVersion::SPIRV1_4,
+            0x0001_0500 => SpirvVersion::SPIRV1_5,
+            0x0001_0600 => SpirvVersion::SPIRV1_6,
+            _ => return None,
+        })
+    }
+
+    /// The generator magic number, with the tool id in the high 16 bits and
+    /// the tool version in the low 16 bits.
+    pub fn generator(&self) -> u32 {
+        self.words[2]
+    }
+
+    /// The bound of the ids in the module. All ids are less than the bound.
+    pub fn bound(&self) -> u32 {
+        self.words[3]
+    }
+
+    /// The entry points of the module.
+    pub fn entry_points(&self) -> impl Iterator<Item = EntryPoint> + '_ {
+        self.instructions(OP_ENTRY_POINT)
+            .filter(|operands| operands.len() >= 3)
+            .map(|operands| EntryPoint {
+                execution_model: operands[0],
+                id: operands[1],
+                name: decode_string(&operands[2..]),
+            })
+    }
+
+    /// The values of the `Capability` operands of the `OpCapability` instructions in the module.
+    pub fn capabilities(&self) -> impl Iterator<Item = u32> + '_ {
+        self.instructions(OP_CAPABILITY)
+            .filter_map(|operands| operands.first().copied())
+    }
+
+    /// The module as little-endian bytes, as SPIR-V is usually stored.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    /// Write the module as little-endian bytes.
+    pub fn write_to(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// Write the module as little-endian bytes to a file.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    /// The operands of the instructions with the given opcode.
+    fn instructions(&self, opcode: u32) -> impl Iterator<Item = &[u32]> + '_ {
+        let mut index = HEADER_LENGTH;
+        std::iter::from_fn(move || {
+            let instruction = self.words.get(index..)?;
+            let word_count = (instruction[0] >> 16) as usize;
+            index += word_count;
+            Some(&instruction[..word_count])
+        })
+        .filter(move |instruction| instruction[0] & 0xffff == opcode)
+        .map(|instruction| &instruction[1..])
+    }
+}
+
+/// Decode a nul-terminated literal string.
+fn decode_string(words: &[u32]) -> String {
+    let bytes: Vec<u8> = words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .take_while(|&byte| byte != 0)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+impl Deref for SpirvModule {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        &self.words
+    }
+}
+
+impl AsRef<[u32]> for SpirvModule {
+    fn as_ref(&self) -> &[u32] {
+        &self.words
+    }
+}
+
+impl From<SpirvModule> for Vec<u32> {
+    fn from(module: SpirvModule) -> Self {
+        module.words
+    }
+}
+
+impl TryFrom<Vec<u32>> for SpirvModule {
+    type Error = GlslangError;
+
+    fn try_from(words: Vec<u32>) -> Result<Self, Self::Error> {
+        Self::from_words(words)
+    }
+}
+
+/// Disassemble a SPIR-V module into the same text as `glslangValidator -H`.
+///
+/// Returns [`GlslangError::InvalidSpirv`] if the words are not a well-formed module, or if an
+/// instruction is too short for its opcode or uses an id that is not less than the bound.
+pub fn disassemble(words: &[u32]) -> Result<String, GlslangError> {
+    check_module(words)?;
+
+    unsafe {
+        // glslang trusts the operands of every instruction, so the shim checks them first.
+        let mut text = std::ptr::null_mut();
+        let error = sys::glslang_rs_spirv_disassemble(words.as_ptr(), words.len(), &mut text);
+        if !error.is_null() {
+            let message = CStr::from_ptr(error).to_string_lossy().into_owned();
+            sys::glslang_rs_free_string(error);
+            return Err(GlslangError::InvalidSpirv(message));
+        }
+
+        let disassembly = CStr::from_ptr(text).to_string_lossy().into_owned();
+        sys::glslang_rs_free_string(text);
+        Ok(disassembly)
+    }
+}
+
+bitflags! {
+    /// The transformations applied by [`remap`], matching the options of `spirv-remap`.
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+    pub struct RemapOptions: u32 {
+        /// Strip debug information such as names and line information.
+        const STRIP = 1 << 0;
+        /// Canonicalize the ids of types.
+        const MAP_TYPES = 1 << 1;
+        /// Canonicalize ids based on their debug names.
+        const MAP_NAMES = 1 << 2;
+        /// Canonicalize the ids within functions.
+        const MAP_FUNCS = 1 << 3;
+        /// Remove unused functions.
+        const DCE_FUNCS = 1 << 4;
+        /// Remove unused variables.
+        const DCE_VARS = 1 << 5;
+        /// Remove unused types.
+        const DCE_TYPES = 1 << 6;
+        /// Remove redundant loads and stores of function local variables.
+        const OPT_LOADSTORE = 1 << 7;
+        /// Forward loads and stores. This is experimental and may produce invalid SPIR-V.
+        const OPT_FWD_LS = 1 << 8;
+        /// Canonicalize all ids.
+        const MAP_ALL = Self::MAP_TYPES.bits() | Self::MAP_NAMES.bits() | Self::MAP_FUNCS.bits();
+        /// Remove all dead code.
+        const DCE_ALL = Self::DCE_FUNCS.bits() | Self::DCE_VARS.bits() | Self::DCE_TYPES.bits();
+        /// Apply all safe optimizations.
+        const OPT_ALL = Self::OPT_LOADSTORE.bits();
+        /// Canonicalize ids, remove dead code and optimize, keeping debug information.
+        const ALL_BUT_STRIP = Self::MAP_ALL.bits() | Self::DCE_ALL.bits() | Self::OPT_ALL.bits();
+        /// Apply every transformation except the experimental ones.
+        const DO_EVERYTHING = Self::STRIP.bits() | Self::ALL_BUT_STRIP.bits();
+    }
+}
+
+/// Remap a SPIR-V module with glslang's remapper, like `spirv-remap`.
+///
+/// Canonicalizing ids and stripping debug information makes similar modules
+/// compress much better than the output of the compiler.
+pub fn remap(words: &[u32], options: RemapOptions) -> Result<Vec<u32>, GlslangError> {
+    check_module(words)?;
+
+    let mut remapped = words.to_vec();
+    let mut size = remapped.len();
+
+    unsafe {
+        let error = sys::glslang_rs_spirv_remap(remapped.as_mut_ptr(), &mut size, options.bits());
+        if !error.is_null() {
+            let message = CStr::from_ptr(error).to_string_lossy().into_owned();
+            sys::glslang_rs_free_string(error);
+            return Err(GlslangError::RemapError(message));
+        }
+    }
+
+    remapped.truncate(size);
+    Ok(remapped)
+}
+
+/// Check that the words have a valid SPIR-V header and a sequence of complete instructions.
+pub(crate) fn check_module(words: &[u32]) -> Result<(), GlslangError> {
+    if words.len() < HEADER_LENGTH {
+        return Err(GlslangError::InvalidSpirv(format!(
+            "module is {} words long, but the header is {HEADER_LENGTH} words",
+            words.len()
+        )));
+    }
+
+    if words[0] != MAGIC_NUMBER {
+        return Err(GlslangError::InvalidSpirv(format!(
+            "bad magic number {:#010x}",
+            words[0]
+        )));
+    }
+
+    if words[3] > MAX_ID_BOUND {
+        return Err(GlslangError::InvalidSpirv(format!(
+            "id bound {} is larger than {MAX_ID_BOUND}",
+            words[3]
+        )));
+    }
+
+    if words[4] != 0 {
+        return Err(GlslangError::InvalidSpirv(format!(
+            "bad schema {}, must be 0",
+            words[4]
+        )));
+    }
+
+    let mut index = HEADER_LENGTH;
+    while index < words.len() {
+        let word_count = (words[index] >> 16) as usize;
+        if word_count == 0 {
+            return Err(GlslangError::InvalidSpirv(format!(
+                "instruction at word {index} has a word count of zero"
+            )));
+        }
+
+        if index + word_count > words.len() {
+            return Err(GlslangError::InvalidSpirv(format!(
+                "instruction at word {index} ends after the end of the module"
+            )));
+        }
+
+        index += word_count;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn compile_fragment(source: &str) -> SpirvModule {
+        let compiler = Compiler::acquire().unwrap();
+        let source = ShaderSource::from(source);
+
+        let input = ShaderInput::new(
+            &source,
+            ShaderStage::Fragment,
+            &CompilerOptions::default(),
+            None,
+            None,
+        )
+        .expect("target");
+        let shader = Shader::new(compiler, input).expect("shader init");
+
+        let mut program = Program::new(compiler);
+        program.add_shader(&shader).expect("add shader");
+        program.compile(ShaderStage::Fragment).expect("compile")
+    }
+
+    #[test]
+    pub fn test_disassemble() {
+        let code = compile_fragment(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        let text = disassemble(&code).expect("disassemble");
+        assert!(text.starts_with("// Module Version"));
+        assert!(text.contains("Capability Shader"));
+        assert!(text.contains("EntryPoint Fragment"));
+
+        assert!(matches!(
+            disassemble(&code[..3]),
+            Err(GlslangError::InvalidSpirv(_))
+        ));
+        // OpCapability is two words long.
+        assert!(matches!(
+            disassemble(&code[..HEADER_LENGTH + 1]),
+            Err(GlslangError::InvalidSpirv(_))
+        ));
+
+        let mut bad_magic = code.to_vec();
+        bad_magic[0] = bad_magic[0].swap_bytes();
+        assert!(matches!(
+            disassemble(&bad_magic),
+            Err(GlslangError::InvalidSpirv(_))
+        ));
+
+        let mut bad_schema = code.to_vec();
+        bad_schema[4] = 1;
+        assert!(matches!(
+            disassemble(&bad_schema),
+            Err(GlslangError::InvalidSpirv(_))
+        ));
+
+        // Every id in the module is at least 1.
+        let mut out_of_bounds = code.to_vec();
+        out_of_bounds[3] = 1;
+        assert!(matches!(
+            disassemble(&out_of_bounds),
+            Err(GlslangError::InvalidSpirv(_))
+        ));
+
+        // OpCapability Shader, then an OpTypeVector %1 of %2 without a component count.
+        let mut short_instruction = code[..HEADER_LENGTH].to_vec();
+        short_instruction[3] = 3;
+        short_instruction.extend([(2 << 16) | 17, 1, (3 << 16) | 23, 1, 2]);
+        assert!(matches!(
+            disassemble(&short_instruction),
+            Err(GlslangError::InvalidSpirv(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_remap() {
+        let code = compile_fragment(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+vec4 unused(vec4 value) {
+    return value * 2.0;
+}
+
+void main() {
+    vec4 named_value = vec4(1.0);
+    color = named_value;
+}
+        "#,
+        );
+
+        let count_names = |code: &[u32]| {
+            let module = rspirv::dr::load_words(code).expect("parse");
+            module.debug_names.len()
+        };
+        assert!(count_names(&code) > 0);
+
+        for options in [
+            RemapOptions::STRIP,
+            RemapOptions::MAP_ALL,
+            RemapOptions::DCE_ALL,
+            RemapOptions::ALL_BUT_STRIP,
+            RemapOptions::DO_EVERYTHING,
+        ] {
+            let remapped = remap(&code, options).expect("remap");
+            let module = rspirv::dr::load_words(&remapped).expect("parse");
+            assert_eq!(module.entry_points.len(), 1);
+
+            if options.contains(RemapOptions::STRIP) {
+                assert_eq!(count_names(&remapped), 0);
+            } else {
+                assert!(count_names(&remapped) > 0);
+            }
+
+            if options.contains(RemapOptions::DCE_FUNCS) {
+                assert_eq!(module.functions.len(), 1);
+            }
+        }
+
+        assert!(matches!(
+            remap(&code[..3], RemapOptions::DO_EVERYTHING),
+            Err(GlslangError::InvalidSpirv(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_spirv_module() {
+        let module = compile_fragment(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+        "#,
+        );
+
+        assert_eq!(module.version(), Some(SpirvVersion::SPIRV1_0));
+        // glslang's registered tool id is 8.
+        assert_eq!(module.generator() >> 16, 8);
+        assert!(module.bound() > 0);
+
+        let entry_points: Vec<EntryPoint> = module.entry_points().collect();
+        assert_eq!(entry_points.len(), 1);
+        assert_eq!(entry_points[0].name, "main");
+        assert_eq!(entry_points[0].stage(), Some(ShaderStage::Fragment));
+
+        // Capability Shader
+        assert!(module.capabilities().any(|capability| capability == 1));
+
+        let bytes = module.to_bytes();
+        assert_eq!(bytes.len(), module.len() * 4);
+        assert_eq!(bytes[..4], [0x03, 0x02, 0x23, 0x07]);
+        assert_eq!(SpirvModule::from_bytes(&bytes).expect("module"), module);
+
+        let mut written = Vec::new();
+        module.write_to(&mut written).expect("write");
+        assert_eq!(written, bytes);
+
+        let big_endian: Vec<u8> = module.iter().flat_map(|word| word.to_be_bytes()).collect();
+        assert_eq!(
+            SpirvModule::from_bytes(&big_endian).expect("module"),
+            module
+        );
+
+        assert!(matches!(
+            SpirvModule::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(GlslangError::InvalidSpirv(_))
+        ));
+        assert!(matches!(
+            SpirvModule::from_bytes(&[0; 20]),
+            Err(GlslangError::InvalidSpirv(_))
+        ));
+    }
+}
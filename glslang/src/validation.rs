@@ -0,0 +1,330 @@
+use crate::ctypes::ShaderStage;
+use crate::error::GlslangError;
+use crate::{Compiler, Program, Shader, ShaderInput};
+
+/// The severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticSeverity {
+    /// An error that prevents the shader from compiling.
+    Error,
+    /// A warning that does not prevent the shader from compiling.
+    Warning,
+    /// An informational note.
+    Note,
+}
+
+/// A single message from the glslang info log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The stage of the shader the diagnostic is for, or `None` if it was reported when linking.
+    pub stage: Option<ShaderStage>,
+    /// The severity of the diagnostic.
+    pub severity: DiagnosticSeverity,
+    /// The name or index of the source string the diagnostic refers to, if any.
+    pub source: Option<String>,
+    /// The line the diagnostic refers to, if any.
+    pub line: Option<u32>,
+    /// The column the diagnostic refers to, if column display was enabled with
+    /// [`ShaderMessage::DISPLAY_ERROR_COLUMN`](crate::ShaderMessage::DISPLAY_ERROR_COLUMN).
+    pub column: Option<u32>,
+    /// The message of the diagnostic.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Parse the diagnostics in a glslang info log.
+    ///
+    /// Lines that are not diagnostics, and the summary of the error count, are skipped.
+    pub(crate) fn parse_log(stage: Option<ShaderStage>, log: &str) -> Vec<Diagnostic> {
+        log.lines()
+            .filter_map(|line| Self::parse_line(stage, line))
+            .collect()
+    }
+
+    fn parse_line(stage: Option<ShaderStage>, line: &str) -> Option<Diagnostic> {
+        let (severity, rest) = [
+            ("ERROR: ", DiagnosticSeverity::Error),
+            ("INTERNAL ERROR: ", DiagnosticSeverity::Error),
+            ("UNIMPLEMENTED: ", DiagnosticSeverity::Error),
+            ("WARNING: ", DiagnosticSeverity::Warning),
+            ("NOTE: ", DiagnosticSeverity::Note),
+        ]
+        .into_iter()
+        .find_map(|(prefix, severity)| line.strip_prefix(prefix).map(|rest| (severity, rest)))?;
+
+        if rest.ends_with("compilation errors.  No code generated.") {
+            return None;
+        }
+
+        let mut diagnostic = Diagnostic {
+            stage,
+            severity,
+            source: None,
+            line: None,
+            column: None,
+            message: rest.trim().to_string(),
+        };
+
+        // Locations are `source:line:` or `source:line:column:`, where the source may itself
+        // contain colons, so find the first colon that is followed by a line number.
+        for (index, _) in rest.match_indices(':') {
+            let Some((line, after)) = Self::parse_number(&rest[index + 1..]) else {
+                continue;
+            };
+
+            let (column, after) = match Self::parse_number(after) {
+                Some((column, after)) => (Some(column), after),
+                None => (None, after),
+            };
+
+            diagnostic.source = Some(rest[..index].to_string());
+            diagnostic.line = Some(line);
+            diagnostic.column = column;
+            diagnostic.message = after.trim().to_string();
+            break;
+        }
+
+        Some(diagnostic)
+    }
+
+    /// Parse the SPIRV-Tools validation errors reported when validating SPIR-V.
+    ///
+    /// The errors are logged like glslang does, as `SPIRV-Tools Validation Errors` followed by
+    /// the output of the validator, which may continue over several lines.
+    pub(crate) fn parse_spirv_messages(stage: ShaderStage, messages: &str) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let mut validation_errors = false;
+
+        for line in messages.lines() {
+            if line == "error: SPIRV-Tools Validation Errors" {
+                validation_errors = true;
+            } else if !validation_errors {
+                continue;
+            } else if let Some(message) = line.strip_prefix("error: ") {
+                diagnostics.push(Diagnostic {
+                    stage: Some(stage),
+                    severity: DiagnosticSeverity::Error,
+                    source: None,
+                    line: None,
+                    column: None,
+                    message: message.trim_end().to_string(),
+                });
+            } else if let Some(diagnostic) = diagnostics.last_mut() {
+                diagnostic.message.push('\n');
+                diagnostic.message.push_str(line.trim_end());
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Parse a number followed by a colon, returning the number and the rest of the string.
+    fn parse_number(string: &str) -> Option<(u32, &str)> {
+        let (number, rest) = string.split_once(':')?;
+        Some((number.parse().ok()?, rest))
+    }
+}
+
+/// The result of validating shaders with [`Compiler::validate`](crate::Compiler::validate).
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    success: bool,
+    /// The diagnostics of all shaders in the order they were given, followed by the
+    /// diagnostics from linking.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// Whether all shaders were parsed, and linked if requested, without errors.
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    /// The diagnostics with [`DiagnosticSeverity::Error`].
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == DiagnosticSeverity::Error)
+    }
+
+    /// The diagnostics with [`DiagnosticSeverity::Warning`].
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == DiagnosticSeverity::Warning)
+    }
+}
+
+impl ValidationReport {
+    pub(crate) fn validate<'input>(
+        compiler: &Compiler,
+        inputs: impl IntoIterator<Item = ShaderInput<'input>>,
+        link: bool,
+    ) -> Result<ValidationReport, GlslangError> {
+        let mut report = ValidationReport {
+            success: true,
+            diagnostics: Vec::new(),
+        };
+
+        let mut shaders = Vec::new();
+
+        for input in inputs {
+            let stage = input.input.stage;
+
+            match Shader::new(compiler, input) {
+                Ok(shader) => {
+                    report
+                        .diagnostics
+                        .extend(Diagnostic::parse_log(Some(stage), &shader.get_log()));
+                    shaders.push(shader);
+                }
+                Err(GlslangError::ParseError(log)) => {
+                    report
+                        .diagnostics
+                        .extend(Diagnostic::parse_log(Some(stage), &log.log));
+                    report.success = false;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if link && report.success && !shaders.is_empty() {
+            let mut program = Program::new(compiler);
+            for shader in &shaders {
+                program.add_shader(shader)?;
+            }
+
+            let messages = program.link_messages();
+            report.success = program.link_in_place(messages).is_ok();
+            report
+                .diagnostics
+                .extend(Diagnostic::parse_log(None, &program.get_log()));
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::{CompilerOptions, ShaderSource, Target};
+
+    #[test]
+    pub fn test_parse_log() {
+        let log = "WARNING: 0:3: 'deprecated' : feature is deprecated\n\
+            ERROR: C:\\shaders\\main.frag:7:12: 'foo' : undeclared identifier\n\
+            ERROR: Linking fragment stage: Missing entry point: Each stage requires one entry point\n\
+            ERROR: 1 compilation errors.  No code generated.\n";
+
+        let diagnostics = Diagnostic::parse_log(Some(ShaderStage::Fragment), log);
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    stage: Some(ShaderStage::Fragment),
+                    severity: DiagnosticSeverity::Warning,
+                    source: Some("0".to_string()),
+                    line: Some(3),
+                    column: None,
+                    message: "'deprecated' : feature is deprecated".to_string(),
+                },
+                Diagnostic {
+                    stage: Some(ShaderStage::Fragment),
+                    severity: DiagnosticSeverity::Error,
+                    source: Some("C:\\shaders\\main.frag".to_string()),
+                    line: Some(7),
+                    column: Some(12),
+                    message: "'foo' : undeclared identifier".to_string(),
+                },
+                Diagnostic {
+                    stage: Some(ShaderStage::Fragment),
+                    severity: DiagnosticSeverity::Error,
+                    source: None,
+                    line: None,
+                    column: None,
+                    message: "Linking fragment stage: Missing entry point: Each stage requires one entry point".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_parse_spirv_messages() {
+        let messages = "warning: unused result\n\
+            error: SPIRV-Tools Validation Errors\n\
+            error: ID '9[%9]' has not been defined\n  %11 = OpLoad %v4float %9\n";
+
+        let diagnostics = Diagnostic::parse_spirv_messages(ShaderStage::Fragment, messages);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                stage: Some(ShaderStage::Fragment),
+                severity: DiagnosticSeverity::Error,
+                source: None,
+                line: None,
+                column: None,
+                message: "ID '9[%9]' has not been defined\n  %11 = OpLoad %v4float %9".to_string(),
+            }]
+        );
+
+        let diagnostics =
+            Diagnostic::parse_spirv_messages(ShaderStage::Fragment, "warning: unused result\n");
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    pub fn test_validate() {
+        let compiler = Compiler::acquire().unwrap();
+
+        let vertex = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+    gl_Position = vec4(0.0);
+}
+        "#,
+        );
+
+        let fragment = ShaderSource::from(
+            r#"
+#version 450
+
+layout(location = 0) in vec4 color;
+layout(location = 0) out vec4 result;
+
+void main() {
+    result = undeclared(color);
+}
+        "#,
+        );
+
+        let options = CompilerOptions {
+            target: Target::None(None),
+            ..CompilerOptions::default()
+        };
+
+        let inputs = [
+            ShaderInput::new(&vertex, ShaderStage::Vertex, &options, None, None).expect("target"),
+            ShaderInput::new(&fragment, ShaderStage::Fragment, &options, None, None)
+                .expect("target"),
+        ];
+
+        let report = compiler.validate(inputs, true).expect("validate");
+        assert!(!report.success());
+
+        let error = report.errors().next().expect("error");
+        assert_eq!(error.stage, Some(ShaderStage::Fragment));
+        assert_eq!(error.line, Some(8));
+
+        let input =
+            ShaderInput::new(&vertex, ShaderStage::Vertex, &options, None, None).expect("target");
+        let report = compiler.validate([input], true).expect("validate");
+        assert!(report.success());
+        assert_eq!(report.errors().count(), 0);
+    }
+}